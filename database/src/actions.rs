@@ -10,6 +10,10 @@ pub struct Model {
     pub rid: i32,
     pub uid: i64,
     pub created_at: DateTime,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub note: Option<String>,
+    /// Учтено в `users.contributions` и числе участников (вклад из теневого бана не учитывается)
+    pub counted: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
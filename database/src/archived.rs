@@ -1,5 +1,6 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.2
 
+use super::sea_orm_active_enums::Platform;
 use sea_orm::entity::prelude::*;
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
@@ -12,14 +13,15 @@ pub struct Model {
     pub created_by: i64,
     pub created_at: DateTime,
     pub contributors: i32,
+    pub platform: Platform,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
     #[sea_orm(
         belongs_to = "super::videos::Entity",
-        from = "Column::Ytid",
-        to = "super::videos::Column::Ytid",
+        from = "(Column::Platform, Column::Ytid)",
+        to = "(super::videos::Column::Platform, super::videos::Column::Ytid)",
         on_update = "NoAction",
         on_delete = "NoAction"
     )]
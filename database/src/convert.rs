@@ -0,0 +1,25 @@
+//! Преобразования между типами БД и крейта `youtube`.
+
+use crate::sea_orm_active_enums::Platform;
+
+impl From<youtube::Platform> for Platform {
+    fn from(platform: youtube::Platform) -> Self {
+        match platform {
+            youtube::Platform::YouTube => Platform::Youtube,
+            youtube::Platform::Twitch => Platform::Twitch,
+            youtube::Platform::Vk => Platform::Vk,
+            youtube::Platform::Rutube => Platform::Rutube,
+        }
+    }
+}
+
+impl From<Platform> for youtube::Platform {
+    fn from(platform: Platform) -> Self {
+        match platform {
+            Platform::Youtube => youtube::Platform::YouTube,
+            Platform::Twitch => youtube::Platform::Twitch,
+            Platform::Vk => youtube::Platform::Vk,
+            Platform::Rutube => youtube::Platform::Rutube,
+        }
+    }
+}
@@ -0,0 +1,20 @@
+use super::sea_orm_active_enums::FilterAction;
+use super::sea_orm_active_enums::FilterKind;
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "filters")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub kind: FilterKind,
+    pub pattern: String,
+    pub action: FilterAction,
+    pub created_by: i64,
+    pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
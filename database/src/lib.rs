@@ -4,7 +4,15 @@ pub mod prelude;
 
 pub mod actions;
 pub mod archived;
+pub mod banned_channels;
+pub mod banned_users;
+pub mod filters;
 pub mod moderators;
+pub mod notifications;
 pub mod requests;
+pub mod sea_orm_active_enums;
+pub mod submissions;
 pub mod users;
 pub mod videos;
+
+mod convert;
@@ -2,7 +2,12 @@
 
 pub use super::actions::Entity as Actions;
 pub use super::archived::Entity as Archived;
+pub use super::banned_channels::Entity as BannedChannels;
+pub use super::banned_users::Entity as BannedUsers;
+pub use super::filters::Entity as Filters;
 pub use super::moderators::Entity as Moderators;
+pub use super::notifications::Entity as Notifications;
 pub use super::requests::Entity as Requests;
+pub use super::submissions::Entity as Submissions;
 pub use super::users::Entity as Users;
 pub use super::videos::Entity as Videos;
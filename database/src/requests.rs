@@ -1,5 +1,6 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.2
 
+use super::sea_orm_active_enums::{Platform, Status};
 use sea_orm::entity::prelude::*;
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
@@ -9,6 +10,12 @@ pub struct Model {
     pub id: i32,
     pub ytid: String,
     pub viewed_at: Option<DateTime>,
+    pub status: Status,
+    pub flagged: bool,
+    pub start_at: Option<i32>,
+    pub end_at: Option<i32>,
+    pub platform: Platform,
+    pub hidden: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -17,8 +24,8 @@ pub enum Relation {
     Actions,
     #[sea_orm(
         belongs_to = "super::videos::Entity",
-        from = "Column::Ytid",
-        to = "super::videos::Column::Ytid",
+        from = "(Column::Platform, Column::Ytid)",
+        to = "(super::videos::Column::Platform, super::videos::Column::Ytid)",
         on_update = "NoAction",
         on_delete = "Cascade"
     )]
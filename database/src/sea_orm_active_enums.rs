@@ -0,0 +1,43 @@
+use sea_orm::entity::prelude::*;
+
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(16))")]
+pub enum FilterAction {
+    #[sea_orm(string_value = "ban")]
+    Ban,
+    #[sea_orm(string_value = "flag")]
+    Flag,
+    #[sea_orm(string_value = "reject")]
+    Reject,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(16))")]
+pub enum FilterKind {
+    #[sea_orm(string_value = "keyword")]
+    Keyword,
+    #[sea_orm(string_value = "regex")]
+    Regex,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(16))")]
+pub enum Platform {
+    #[sea_orm(string_value = "rutube")]
+    Rutube,
+    #[sea_orm(string_value = "twitch")]
+    Twitch,
+    #[sea_orm(string_value = "vk")]
+    Vk,
+    #[sea_orm(string_value = "youtube")]
+    Youtube,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(16))")]
+pub enum Status {
+    #[sea_orm(string_value = "available")]
+    Available,
+    #[sea_orm(string_value = "unavailable")]
+    Unavailable,
+}
@@ -9,6 +9,7 @@ pub struct Model {
     pub id: i64,
     pub created_at: DateTime,
     pub contributions: i32,
+    pub notify: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
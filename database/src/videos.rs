@@ -1,5 +1,6 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.2
 
+use super::sea_orm_active_enums::Platform;
 use sea_orm::entity::prelude::*;
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
@@ -9,6 +10,12 @@ pub struct Model {
     pub ytid: String,
     pub title: String,
     pub banned: bool,
+    pub channel: Option<String>,
+    pub channel_url: Option<String>,
+    pub thumbnail: Option<String>,
+    pub duration: Option<i32>,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub platform: Platform,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -1,6 +1,19 @@
 pub use sea_orm_migration::prelude::*;
 
 mod m20241211_182453_create_tables;
+mod m20261017_120000_add_video_metadata;
+mod m20261017_130000_add_request_status;
+mod m20261017_140000_create_banned_channels;
+mod m20261017_150000_create_filters;
+mod m20261017_160000_add_request_offsets;
+mod m20261017_170000_add_video_platform;
+mod m20261017_180000_create_submissions;
+mod m20261017_190000_create_banned_users;
+mod m20261017_200000_add_shadow_bans;
+mod m20261017_210000_add_action_notes;
+mod m20261017_220000_add_user_notify;
+mod m20261018_100000_create_notifications;
+mod m20261018_110000_add_action_counted;
 
 pub struct Migrator;
 
@@ -9,6 +22,19 @@ impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
         vec![
             Box::new(m20241211_182453_create_tables::Migration),
+            Box::new(m20261017_120000_add_video_metadata::Migration),
+            Box::new(m20261017_130000_add_request_status::Migration),
+            Box::new(m20261017_140000_create_banned_channels::Migration),
+            Box::new(m20261017_150000_create_filters::Migration),
+            Box::new(m20261017_160000_add_request_offsets::Migration),
+            Box::new(m20261017_170000_add_video_platform::Migration),
+            Box::new(m20261017_180000_create_submissions::Migration),
+            Box::new(m20261017_190000_create_banned_users::Migration),
+            Box::new(m20261017_200000_add_shadow_bans::Migration),
+            Box::new(m20261017_210000_add_action_notes::Migration),
+            Box::new(m20261017_220000_add_user_notify::Migration),
+            Box::new(m20261018_100000_create_notifications::Migration),
+            Box::new(m20261018_110000_add_action_counted::Migration),
         ]
     }
 }
@@ -0,0 +1,44 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Videos::Table)
+                    .add_column_if_not_exists(string_null(Videos::Channel))
+                    .add_column_if_not_exists(string_null(Videos::ChannelUrl))
+                    .add_column_if_not_exists(string_null(Videos::Thumbnail))
+                    .add_column_if_not_exists(integer_null(Videos::Duration))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Videos::Table)
+                    .drop_column(Videos::Channel)
+                    .drop_column(Videos::ChannelUrl)
+                    .drop_column(Videos::Thumbnail)
+                    .drop_column(Videos::Duration)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Videos {
+    Table,
+    Channel,
+    ChannelUrl,
+    Thumbnail,
+    Duration
+}
@@ -0,0 +1,37 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(BannedChannels::Table)
+                    .if_not_exists()
+                    .col(string_uniq(BannedChannels::Url).primary_key())
+                    .col(string_null(BannedChannels::Name))
+                    .col(big_integer(BannedChannels::BannedBy))
+                    .col(timestamp(BannedChannels::CreatedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(BannedChannels::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum BannedChannels {
+    Table,
+    Url,
+    Name,
+    BannedBy,
+    CreatedAt
+}
@@ -0,0 +1,69 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Filters
+        manager
+            .create_table(
+                Table::create()
+                    .table(Filters::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Filters::Id))
+                    .col(string_len(Filters::Kind, 16))
+                    .col(string(Filters::Pattern))
+                    .col(string_len(Filters::Action, 16))
+                    .col(big_integer(Filters::CreatedBy))
+                    .col(timestamp(Filters::CreatedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await?;
+        // Requests
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Requests::Table)
+                    .add_column_if_not_exists(boolean(Requests::Flagged).default(Expr::value(false)))
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Requests
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Requests::Table)
+                    .drop_column(Requests::Flagged)
+                    .to_owned(),
+            )
+            .await?;
+        // Filters
+        manager
+            .drop_table(Table::drop().table(Filters::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Filters {
+    Table,
+    Id,
+    Kind,
+    Pattern,
+    Action,
+    CreatedBy,
+    CreatedAt
+}
+
+#[derive(DeriveIden)]
+enum Requests {
+    Table,
+    Flagged
+}
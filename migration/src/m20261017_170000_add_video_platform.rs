@@ -0,0 +1,153 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Ключ видео теперь (платформа, ID на платформе). Существующие записи — YouTube.
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Внешние ключи ссылаются на старый первичный ключ
+        drop_foreign_keys(manager).await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Videos::Table)
+                    .add_column_if_not_exists(string_len(Videos::Platform, 16).default("youtube"))
+                    .modify_column(string_len(Videos::Ytid, 128))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "ALTER TABLE videos DROP CONSTRAINT IF EXISTS videos_ytid_key; \
+                 ALTER TABLE videos DROP CONSTRAINT videos_pkey; \
+                 ALTER TABLE videos ADD PRIMARY KEY (platform, ytid);",
+            )
+            .await?;
+        // Столбцы называются одинаково во всех трёх таблицах
+        for table in [Requests::Table.into_iden(), Archived::Table.into_iden()] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .add_column_if_not_exists(string_len(Videos::Platform, 16).default("youtube"))
+                        .modify_column(string_len(Videos::Ytid, 128))
+                        .to_owned(),
+                )
+                .await?;
+        }
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_videos_ytid_requests")
+                    .from(Requests::Table, (Requests::Platform, Requests::Ytid))
+                    .to(Videos::Table, (Videos::Platform, Videos::Ytid))
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_videos_ytid_archived")
+                    .from(Archived::Table, (Archived::Platform, Archived::Ytid))
+                    .to(Videos::Table, (Videos::Platform, Videos::Ytid))
+                    .on_delete(ForeignKeyAction::NoAction)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Видео других платформ в старую схему не помещаются
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "DELETE FROM archived WHERE platform <> 'youtube'; \
+                 DELETE FROM requests WHERE platform <> 'youtube'; \
+                 DELETE FROM videos WHERE platform <> 'youtube';",
+            )
+            .await?;
+        drop_foreign_keys(manager).await?;
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "ALTER TABLE videos DROP CONSTRAINT videos_pkey; \
+                 ALTER TABLE videos ADD PRIMARY KEY (ytid);",
+            )
+            .await?;
+        for table in [Videos::Table.into_iden(), Requests::Table.into_iden(), Archived::Table.into_iden()] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .drop_column(Videos::Platform)
+                        .modify_column(string_len(Videos::Ytid, 11))
+                        .to_owned(),
+                )
+                .await?;
+        }
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_videos_ytid_requests")
+                    .from(Requests::Table, Requests::Ytid)
+                    .to(Videos::Table, Videos::Ytid)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_videos_ytid_archived")
+                    .from(Archived::Table, Archived::Ytid)
+                    .to(Videos::Table, Videos::Ytid)
+                    .on_delete(ForeignKeyAction::NoAction)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+async fn drop_foreign_keys(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    manager
+        .drop_foreign_key(
+            ForeignKey::drop()
+                .name("fk_videos_ytid_requests")
+                .table(Requests::Table)
+                .to_owned(),
+        )
+        .await?;
+    manager
+        .drop_foreign_key(
+            ForeignKey::drop()
+                .name("fk_videos_ytid_archived")
+                .table(Archived::Table)
+                .to_owned(),
+        )
+        .await
+}
+
+#[derive(DeriveIden)]
+enum Videos {
+    Table,
+    Platform,
+    Ytid
+}
+
+#[derive(DeriveIden)]
+enum Requests {
+    Table,
+    Platform,
+    Ytid
+}
+
+#[derive(DeriveIden)]
+enum Archived {
+    Table,
+    Platform,
+    Ytid
+}
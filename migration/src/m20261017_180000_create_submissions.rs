@@ -0,0 +1,45 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Submissions::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Submissions::Id))
+                    .col(big_integer(Submissions::Uid))
+                    .col(timestamp(Submissions::CreatedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_submissions_uid_created_at")
+                    .table(Submissions::Table)
+                    .col(Submissions::Uid)
+                    .col(Submissions::CreatedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Submissions::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Submissions {
+    Table,
+    Id,
+    Uid,
+    CreatedAt
+}
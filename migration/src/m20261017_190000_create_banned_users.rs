@@ -0,0 +1,39 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(BannedUsers::Table)
+                    .if_not_exists()
+                    .col(big_integer_uniq(BannedUsers::Id).primary_key())
+                    .col(string_null(BannedUsers::Reason))
+                    .col(big_integer(BannedUsers::BannedBy))
+                    .col(timestamp(BannedUsers::CreatedAt).default(Expr::current_timestamp()))
+                    .col(timestamp_null(BannedUsers::ExpiresAt))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(BannedUsers::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum BannedUsers {
+    Table,
+    Id,
+    Reason,
+    BannedBy,
+    CreatedAt,
+    ExpiresAt
+}
@@ -0,0 +1,59 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(BannedUsers::Table)
+                    .add_column_if_not_exists(boolean(BannedUsers::Shadow).default(Expr::value(false)))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Requests::Table)
+                    .add_column_if_not_exists(boolean(Requests::Hidden).default(Expr::value(false)))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Requests::Table)
+                    .drop_column(Requests::Hidden)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(BannedUsers::Table)
+                    .drop_column(BannedUsers::Shadow)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum BannedUsers {
+    Table,
+    Shadow
+}
+
+#[derive(DeriveIden)]
+enum Requests {
+    Table,
+    Hidden
+}
@@ -0,0 +1,53 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Notifications::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Notifications::Id))
+                    .col(big_integer(Notifications::Uid))
+                    .col(integer(Notifications::Rid))
+                    .col(string(Notifications::Title))
+                    .col(string(Notifications::Outcome))
+                    .col(timestamp(Notifications::CreatedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await?;
+        // По каждому запросу у пользователя остаётся только последнее событие
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_notifications_uid_rid")
+                    .table(Notifications::Table)
+                    .col(Notifications::Uid)
+                    .col(Notifications::Rid)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Notifications::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Notifications {
+    Table,
+    Id,
+    Uid,
+    Rid,
+    Title,
+    Outcome,
+    CreatedAt
+}
@@ -0,0 +1,59 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Actions::Table)
+                    .add_column_if_not_exists(boolean(Actions::Counted).default(true))
+                    .to_owned(),
+            )
+            .await?;
+        // Предложения из теневого бана в users.contributions не учитывались
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(Actions::Table)
+                    .value(Actions::Counted, false)
+                    .and_where(Expr::col(Actions::Rid).in_subquery(
+                        Query::select()
+                            .column(Requests::Id)
+                            .from(Requests::Table)
+                            .and_where(Expr::col(Requests::Hidden).eq(true))
+                            .to_owned(),
+                    ))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Actions::Table)
+                    .drop_column(Actions::Counted)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Actions {
+    Table,
+    Rid,
+    Counted
+}
+
+#[derive(DeriveIden)]
+enum Requests {
+    Table,
+    Id,
+    Hidden
+}
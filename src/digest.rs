@@ -0,0 +1,204 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::Local;
+use indexmap::IndexMap;
+use sea_orm::{prelude::*, sea_query::OnConflict, Order, QueryOrder, QuerySelect, Set};
+use teloxide::{prelude::*, types::ParseMode, utils::html, RequestError};
+
+use database::{actions, notifications, users};
+use crate::AppState;
+
+/// Что случилось с предложенным видео.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Viewed,
+    Rejected,
+    Skipped,
+}
+
+impl Outcome {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Outcome::Viewed => "viewed",
+            Outcome::Rejected => "rejected",
+            Outcome::Skipped => "skipped",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        Some(match value {
+            "viewed" => Outcome::Viewed,
+            "rejected" => Outcome::Rejected,
+            "skipped" => Outcome::Skipped,
+            _ => return None,
+        })
+    }
+
+    fn line(&self, title: &str) -> String {
+        let title = html::escape(title);
+        match self {
+            Outcome::Viewed => format!("👀 Посмотрели: <b>{title}</b>"),
+            Outcome::Rejected => format!("⛔ Отклонено: <b>{title}</b>"),
+            Outcome::Skipped => format!("⏭ Пропущено: <b>{title}</b>"),
+        }
+    }
+}
+
+/// Ставит в очередь уведомления всем подписанным участникам запроса.
+async fn enqueue(rid: i32, title: &str, outcome: Outcome, state: &AppState) -> anyhow::Result<()> {
+    let uids: Vec<i64> = actions::Entity::find()
+        .select_only()
+        .column(actions::Column::Uid)
+        .filter(actions::Column::Rid.eq(rid))
+        .into_tuple()
+        .all(&state.db).await?;
+    enqueue_for(uids, rid, title, outcome, state).await
+}
+
+/// То же, когда участники уже известны (например, запрос сейчас будет удалён).
+/// Уведомления хранятся в БД до отправки, поэтому переживают перезапуск бота.
+pub async fn enqueue_for(uids: Vec<i64>, rid: i32, title: &str, outcome: Outcome, state: &AppState) -> anyhow::Result<()> {
+    if uids.is_empty() {
+        return Ok(());
+    }
+    let subscribed: Vec<i64> = users::Entity::find()
+        .select_only()
+        .column(users::Column::Id)
+        .filter(users::Column::Id.is_in(uids))
+        .filter(users::Column::Notify.eq(true))
+        .into_tuple()
+        .all(&state.db).await?;
+    if subscribed.is_empty() {
+        return Ok(());
+    }
+    let now = Local::now().naive_local();
+    let pending = subscribed.into_iter().map(|uid| notifications::ActiveModel {
+        uid: Set(uid),
+        rid: Set(rid),
+        title: Set(title.to_string()),
+        outcome: Set(outcome.as_str().to_string()),
+        created_at: Set(now),
+        ..Default::default()
+    });
+    // Повторное событие по тому же запросу заменяет прежнее и переносится в конец
+    notifications::Entity::insert_many(pending)
+        .on_conflict(OnConflict::columns([notifications::Column::Uid, notifications::Column::Rid])
+            .update_columns([notifications::Column::Title, notifications::Column::Outcome, notifications::Column::CreatedAt])
+            .to_owned()
+        ).exec(&state.db).await?;
+    Ok(())
+}
+
+/// `enqueue` для обработчиков модератора: ошибка только логируется.
+/// Действие уже выполнено, и сбой уведомления не должен выдавать его за неудачное.
+pub async fn enqueue_logged(rid: i32, title: &str, outcome: Outcome, state: &AppState) {
+    if let Err(err) = enqueue(rid, title, outcome, state).await {
+        tracing::error!("Caused an exception in digest enqueue due: {err:?}");
+    }
+}
+
+/// Отзывает ещё не отправленные уведомления о событии, которое отменили (например, снятый просмотр).
+pub async fn retract(rid: i32, outcome: Outcome, state: &AppState) -> anyhow::Result<()> {
+    notifications::Entity::delete_many()
+        .filter(notifications::Column::Rid.eq(rid))
+        .filter(notifications::Column::Outcome.eq(outcome.as_str()))
+        .exec(&state.db).await?;
+    Ok(())
+}
+
+/// Периодически рассылает накопленные уведомления.
+pub async fn run(bot: Bot, state: Arc<AppState>, period: Duration) {
+    let mut interval = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+    loop {
+        interval.tick().await;
+        if let Err(err) = send_pending(&bot, &state).await {
+            tracing::error!("Caused an exception in digest due: {err:?}");
+        }
+    }
+}
+
+async fn send_pending(bot: &Bot, state: &AppState) -> anyhow::Result<()> {
+    let pending = notifications::Entity::find()
+        .order_by(notifications::Column::CreatedAt, Order::Asc)
+        .order_by(notifications::Column::Id, Order::Asc)
+        .all(&state.db).await?;
+    for (uid, pending) in group(pending) {
+        let lines = pending.iter().filter_map(|notification| {
+            Some(Outcome::parse(&notification.outcome)?.line(&notification.title))
+        });
+        match bot.send_message(UserId(uid as u64), compose(lines)).parse_mode(ParseMode::Html).await {
+            // Сетевые ошибки временные: попробуем в следующий раз
+            Err(err @ (RequestError::Network(_) | RequestError::RetryAfter(_))) => {
+                tracing::warn!("Can't send digest to {uid}, will retry: {err}");
+                continue;
+            },
+            // Пользователь мог заблокировать бота, остальным всё равно отправляем
+            Err(err) => tracing::warn!("Can't send digest to {uid}: {err}"),
+            Ok(_) => (),
+        }
+        notifications::Entity::delete_many()
+            .filter(notifications::Column::Id.is_in(pending.iter().map(|notification| notification.id)))
+            .exec(&state.db).await?;
+    }
+    Ok(())
+}
+
+/// Группирует уведомления по пользователям, сохраняя порядок.
+fn group(pending: Vec<notifications::Model>) -> IndexMap<i64, Vec<notifications::Model>> {
+    let mut grouped: IndexMap<i64, Vec<notifications::Model>> = IndexMap::new();
+    for notification in pending {
+        grouped.entry(notification.uid).or_default().push(notification);
+    }
+    grouped
+}
+
+fn compose(lines: impl Iterator<Item = String>) -> String {
+    let mut text = String::from("🔔 Новости о предложенных вами видео:");
+    for line in lines {
+        text.push('\n');
+        text.push_str(&line);
+    }
+    text.push_str("\n\nОтключить уведомления: /notify");
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notification(id: i32, uid: i64, rid: i32, outcome: Outcome) -> notifications::Model {
+        notifications::Model {
+            id,
+            uid,
+            rid,
+            title: format!("Video {rid}"),
+            outcome: outcome.as_str().to_string(),
+            created_at: Local::now().naive_local(),
+        }
+    }
+
+    #[test]
+    fn test_group_keeps_order() {
+        let grouped = group(vec![
+            notification(1, 1, 10, Outcome::Viewed),
+            notification(2, 2, 10, Outcome::Viewed),
+            notification(3, 1, 11, Outcome::Rejected),
+        ]);
+        assert_eq!(grouped.keys().copied().collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(grouped[&1].iter().map(|n| n.rid).collect::<Vec<_>>(), [10, 11]);
+    }
+
+    #[test]
+    fn test_outcome_roundtrip() {
+        for outcome in [Outcome::Viewed, Outcome::Rejected, Outcome::Skipped] {
+            assert_eq!(Outcome::parse(outcome.as_str()), Some(outcome));
+        }
+        assert_eq!(Outcome::parse("unknown"), None);
+    }
+
+    #[test]
+    fn test_compose() {
+        let text = compose([Outcome::Viewed.line("<Видео>")].into_iter());
+        assert_eq!(text, "🔔 Новости о предложенных вами видео:\n👀 Посмотрели: <b>&lt;Видео&gt;</b>\n\nОтключить уведомления: /notify");
+    }
+}
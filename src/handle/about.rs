@@ -1,19 +1,24 @@
+use std::sync::Arc;
+
 use chrono::Local;
 use teloxide::{prelude::*, Bot};
 
-use crate::{Rights, CHANNEL, COOLDOWN_DURATION, VERSION};
+use crate::{quota, AppState, Rights, CHANNEL, VERSION};
 
-pub async fn command(bot: Bot, msg: Message, rights: Rights) -> anyhow::Result<()> {
+pub async fn command(bot: Bot, msg: Message, rights: Rights, state: Arc<AppState>) -> anyhow::Result<()> {
+    let cache = state.metadata.stats();
     bot.send_message(msg.chat.id, format!(
             "Doggy-Watch v{VERSION}\n\
             ____________________\n\
             Debug information:\n\
             Rights level: {rights:?}\n\
             Linked channel: {}\n\
-            Cooldown duration: {:?}\n\
+            Quota: {}\n\
+            Metadata cache: {} DB hits, {} memory hits, {} misses\n\
             Server time:\n\
             {}",
-            *CHANNEL, COOLDOWN_DURATION,
+            *CHANNEL, quota::for_rights(&rights),
+            cache.db_hits, cache.memory_hits, cache.misses,
             Local::now().format("%Y-%m-%d %H:%M:%S")
         )).await?;
     Ok(())
@@ -2,34 +2,46 @@ use std::sync::Arc;
 
 use database::*;
 use sea_orm::{prelude::*, EntityTrait, IntoActiveModel, Set};
-use teloxide::{prelude::*, types::ParseMode};
-use tokio::time::Instant;
+use teloxide::{prelude::*, types::{InlineKeyboardButton, InlineKeyboardMarkup, MessageEntityKind, ParseMode}, utils::html};
+use youtube::{format_duration, MetadataError, Platform, SearchResult, VideoLink, VideoMetadata};
 
-use crate::{check_subscription, markup, notify, AppState, DialogueState, MyDialogue, CHANNEL_INVITE_HASH, COOLDOWN_DURATION};
+use super::{bans, channel, my, status};
+use super::filter::{self, Verdict};
+use crate::{check_subscription, markup, notify, quota, AppState, Candidate, DialogueState, MyDialogue, Rights, CHANNEL_INVITE_HASH, MAX_DURATION, MIN_DURATION, SEARCH_RESULTS};
 
-pub async fn message(bot: Bot, msg: Message, dialogue: MyDialogue) -> anyhow::Result<()> {
-    use youtube::*;
-    if let Some(text) = msg.clone().text() {
+/// Сколько видео максимум принимается из одного сообщения.
+const MAX_BATCH_VIDEOS: usize = 10;
+/// Более короткий текст не ищется, а считается ошибкой.
+const MIN_QUERY_LEN: usize = 3;
+/// Заметки длиннее обрезаются.
+const MAX_NOTE_LEN: usize = 300;
+
+pub async fn message(bot: Bot, msg: Message, state: Arc<AppState>, dialogue: MyDialogue) -> anyhow::Result<()> {
+    if msg.text().is_some() || msg.caption().is_some() {
         if let Some(user) = check_subscription(&bot, &msg.clone().from.ok_or(anyhow::anyhow!("Message not from user!"))?.id).await {
             // Get ready!
-            if let Some(ytid) = extract_youtube_video_id(text) {
-                let meta = match get_video_metadata(&ytid).await {
-                    Ok(meta) => meta,
-                    Err(err) => {
-                        tracing::error!("Caused an exception in get_video_metadata due: {err:?}");
-                        bot.send_message(msg.chat.id, "Ошибка при получении метаданных видео!").await?;
-                        return Ok(());
+            let mut links = collect_video_links(&msg);
+            if links.len() > 1 {
+                links.truncate(MAX_BATCH_VIDEOS);
+                return batch_message(bot, msg, &state, dialogue, links, user.id.0).await;
+            }
+            if let Some(link) = links.pop() {
+                match prepare(link, &state).await? {
+                    Ok(video) => {
+                        // Post
+                        bot.send_message(msg.chat.id, confirmation_text(&video))
+                            .parse_mode(ParseMode::Html).reply_markup(confirmation_keyboard(&video)).await?;
+                        dialogue.update(DialogueState::AcceptVideo { uid: user.id.0, video }).await?;
+                    },
+                    Err(reason) => {
+                        bot.send_message(msg.chat.id, reason).await?;
                     },
-                };
-                // Post
-                bot.send_message(msg.chat.id, format!(
-                    "Вы уверены что хотите добавить <b>{}</b>",
-                    meta.title
-                )).parse_mode(ParseMode::Html).reply_markup(markup::inline_yes_or_no()).await?;
-                dialogue.update(DialogueState::AcceptVideo { ytid, uid: user.id.0, title: meta.title }).await?;
+                }
+            } else if let Some(query) = msg.text().map(str::trim).filter(|query| *SEARCH_RESULTS != 0 && !query.starts_with('/') && query.chars().count() >= MIN_QUERY_LEN) {
+                search_message(bot, &msg, &state, dialogue, query, user.id.0).await?;
             } else {
-                tracing::debug!("Not a YouTube video: {:?}", msg);
-                bot.send_message(msg.chat.id, "Это не похоже на YouTube видео... Долбоёб").await?;
+                tracing::debug!("Not a video link: {:?}", msg);
+                bot.send_message(msg.chat.id, "Это не похоже на ссылку на видео... Долбоёб").await?;
             }
         } else {
             let link = if let Some(hash) = CHANNEL_INVITE_HASH.as_ref() {
@@ -45,100 +57,585 @@ pub async fn message(bot: Bot, msg: Message, dialogue: MyDialogue) -> anyhow::Re
     Ok(())
 }
 
+/// Получает метаданные и прогоняет видео через фильтры.
+/// Внутренняя ошибка — объяснение для пользователя.
+async fn prepare(link: VideoLink, state: &AppState) -> anyhow::Result<Result<Candidate, &'static str>> {
+    let VideoLink { platform, id: ytid, start, end } = link;
+    let meta = match state.metadata.get(platform, &ytid, &state.db).await {
+        Ok(meta) => meta,
+        Err(err) => {
+            tracing::error!("Caused an exception in metadata due: {err:?}");
+            return Ok(Err(explain_metadata_error(&err)));
+        },
+    };
+    // Фильтры по названию
+    let flagged = match filter::screen(platform, &ytid, &meta, state).await? {
+        Verdict::Pass => false,
+        Verdict::Flag => true,
+        Verdict::Reject | Verdict::Ban => return Ok(Err("Видео отклонено автоматическим фильтром.")),
+    };
+    let (start, end) = youtube::fit_clip(start, end, meta.duration);
+    Ok(Ok(Candidate { platform, ytid, meta, start, end, selected: true, flagged, note: None }))
+}
+
+/// Отметка отрезка в том виде, в каком она хранится в БД.
+fn offset(seconds: Option<u32>) -> Option<i32> {
+    seconds.and_then(|seconds| i32::try_from(seconds).ok())
+}
+
+fn confirmation_text(video: &Candidate) -> String {
+    let clip = markup::clip_label(offset(video.start), offset(video.end))
+        .map(|clip| format!(" ({clip})"))
+        .unwrap_or_default();
+    let mut text = format!("Вы уверены что хотите добавить <b>{}</b>{clip}", video.meta.title);
+    if let Some(note) = &video.note {
+        text.push_str(&format!("\nЗаметка: {}", html::escape(note)));
+    }
+    text
+}
+
+fn confirmation_keyboard(video: &Candidate) -> InlineKeyboardMarkup {
+    let note = if video.note.is_some() { "Изменить заметку" } else { "Добавить заметку" };
+    InlineKeyboardMarkup::new(vec![
+        vec![InlineKeyboardButton::callback("Да", "yes"), InlineKeyboardButton::callback("Нет", "no")],
+        vec![InlineKeyboardButton::callback(note, "note")],
+    ])
+}
+
+/// Предложение нескольких видео одним сообщением.
+async fn batch_message(bot: Bot, msg: Message, state: &AppState, dialogue: MyDialogue, links: Vec<VideoLink>, uid: u64) -> anyhow::Result<()> {
+    let mut videos = Vec::new();
+    let mut failed = String::new();
+    for link in links {
+        let url = link.platform.video_url(&link.id, link.start);
+        match prepare(link, state).await? {
+            Ok(video) => videos.push(video),
+            Err(reason) => failed.push_str(&format!("\n{url} — {reason}")),
+        }
+    }
+    if videos.is_empty() {
+        bot.send_message(msg.chat.id, format!("Не удалось добавить ни одно видео:{failed}")).await?;
+        return Ok(());
+    }
+    let mut text = format!("Найдено видео: {}\nОтметьте те, которые хотите добавить:", videos.len());
+    if !failed.is_empty() {
+        text.push_str(&format!("\n\nПропущены:{failed}"));
+    }
+    bot.send_message(msg.chat.id, text).reply_markup(batch_keyboard(&videos)).await?;
+    dialogue.update(DialogueState::AcceptVideos { uid, videos }).await?;
+    Ok(())
+}
+
+/// Поиск по названию, если в сообщении нет ссылок.
+async fn search_message(bot: Bot, msg: &Message, state: &AppState, dialogue: MyDialogue, query: &str, uid: u64) -> anyhow::Result<()> {
+    let mut results = match state.search.search(query, *SEARCH_RESULTS).await {
+        Ok(results) => results,
+        Err(err) => {
+            tracing::error!("Caused an exception in search due: {err:?}");
+            bot.send_message(msg.chat.id, explain_metadata_error(&err)).await?;
+            return Ok(());
+        },
+    };
+    // ID берутся со страницы поиска, ссылку из мусора не собираем
+    results.retain(|result| youtube::is_valid_video_id(&result.id));
+    if results.is_empty() {
+        bot.send_message(msg.chat.id, "Это не похоже на ссылку на видео, и по такому названию ничего не нашлось.").await?;
+        return Ok(());
+    }
+    bot.send_message(msg.chat.id, "Ссылки не нашёл, но вот что нашлось по названию. Выберите видео:")
+        .reply_markup(search_keyboard(&results)).await?;
+    dialogue.update(DialogueState::PickVideo { uid, results }).await?;
+    Ok(())
+}
+
+fn search_keyboard(results: &[SearchResult]) -> InlineKeyboardMarkup {
+    let mut keyboard: Vec<Vec<InlineKeyboardButton>> = results.iter().enumerate().map(|(index, result)| {
+        let mut label = markup::truncate(&result.title, 40);
+        if let Some(author) = &result.author_name {
+            label.push_str(&format!(" — {}", markup::truncate(author, 16)));
+        }
+        if let Some(duration) = result.duration {
+            label.push_str(&format!(" ({})", format_duration(duration)));
+        }
+        vec![InlineKeyboardButton::callback(label, format!("pick {index}"))]
+    }).collect();
+    keyboard.push(vec![InlineKeyboardButton::callback("Отменить", "no")]);
+    InlineKeyboardMarkup::new(keyboard)
+}
+
+/// Объяснение для пользователя почему видео не удалось добавить.
+fn explain_metadata_error(err: &MetadataError) -> &'static str {
+    match err {
+        MetadataError::NotFound => "Видео не найдено. Проверьте ссылку!",
+        MetadataError::Private => "Это видео приватное, его не получится посмотреть на стриме.",
+        MetadataError::Unavailable => "Видео удалено или недоступно.",
+        MetadataError::EmbeddingDisabled => "Автор ограничил доступ к видео (приватное или запрещено встраивание).",
+        MetadataError::RateLimited => "Платформа временно ограничила запросы, попробуйте через пару минут.",
+        MetadataError::Unsupported => "Видео с этой платформы пока не принимаются.",
+        MetadataError::Server(_) | MetadataError::Network(_) | MetadataError::InvalidResponse(_) => {
+            "Ошибка при получении метаданных видео! Попробуйте позже."
+        },
+    }
+}
+
+/// Собирает ссылки на видео из сообщения:
+/// ссылки-сущности (в т.ч. скрытые за текстом), затем обычный текст или подпись к медиа.
+fn collect_video_links(msg: &Message) -> Vec<VideoLink> {
+    let entities = msg.parse_entities()
+        .or_else(|| msg.parse_caption_entities())
+        .unwrap_or_default();
+    let mut links: Vec<VideoLink> = Vec::new();
+    let from_entities = entities.iter().filter_map(|entity| match entity.kind() {
+        MessageEntityKind::Url => youtube::parse_video_link(entity.text()),
+        MessageEntityKind::TextLink { url } => youtube::parse_video_link(url.as_str()),
+        _ => None,
+    });
+    let from_text = youtube::find_video_links(msg.text().or(msg.caption()).unwrap_or_default());
+    for link in from_entities.chain(from_text) {
+        if !links.iter().any(|known| known.platform == link.platform && known.id == link.id) {
+            links.push(link);
+        }
+    }
+    links
+}
+
 pub async fn inline(
     bot: Bot,
     q: CallbackQuery,
     msg: Message,
     state: Arc<AppState>,
-    (ytid, uid, title): (String, u64, String),
+    (uid, video): (u64, Candidate),
     dialogue: MyDialogue
 ) -> anyhow::Result<()> {
     let data = q.data.ok_or(anyhow::anyhow!("Inline: Нет данных!"))?;
+    if &data == "note" {
+        bot.edit_message_text(msg.chat.id, msg.id, format!(
+            "Напишите, почему <b>{}</b> стоит посмотреть (до {MAX_NOTE_LEN} символов):", video.meta.title
+        )).parse_mode(ParseMode::Html).reply_markup(InlineKeyboardMarkup::new(vec![
+            vec![InlineKeyboardButton::callback("Без заметки", "skip")],
+        ])).await?;
+        dialogue.update(DialogueState::VideoNote { uid, video }).await?;
+        return Ok(());
+    }
+    // Запрос, от которого можно сразу отказаться
+    let mut withdrawable = None;
     let text = if &data == "yes" {
-        if let Some(last) = state.cooldown.get(&uid) {
-            if last.elapsed() < COOLDOWN_DURATION {
-                bot.edit_message_text(msg.chat.id, msg.id, "Слишком часто!").await?;
-                dialogue.exit().await?;
-                return Ok(());
-            }
+        let rights = state.check_rights(&UserId(uid)).await?;
+        if let Rights::Banned { reason, until } = &rights {
+            bot.edit_message_text(msg.chat.id, msg.id, bans::banned_text(reason, until)).await?;
+            dialogue.exit().await?;
+            return Ok(());
         }
-        match add_video(&ytid, &title, &state).await {
-            Ok(col) => {
-                // Теперь видео создано. Можно приступать к созданию "запроса" и действия
-                match add_action(&col, uid, &state).await {
-                    Ok(_) => {
-                        // Обновляем кул-давн.
-                        state.cooldown.insert(uid, Instant::now());
-                        // Обновляем данные о пользователе
-                        if let Err(err) = add_user(uid, &state).await {
-                            tracing::error!("Caused an exception in add_user due: {err:?}");
-                        }
-                        // Отправляем уведомления
-                        let bot_clone = bot.clone();
-                        tokio::spawn(async move {
-                            let _ = notify(&bot_clone, format!("Добавленно новое видео: <b>{title}</b>!"), &state, vec![UserId(uid)]).await.inspect_err(|err| {
-                                tracing::error!("Caused an exception in notify due: {err:?}");
-                            });
-                        });
-                        "Добавлено!"
-                    },
-                    Err(err) => {
-                        tracing::error!("Caused an exception in add_action due: {err:?}");
-                        &format!("{err:?}")
-                    },
-                }
+        let quota = quota::for_rights(&rights);
+        if let Err(exceeded) = quota::check(uid, quota, &state).await? {
+            bot.edit_message_text(msg.chat.id, msg.id, exceeded.to_string()).await?;
+            dialogue.exit().await?;
+            return Ok(());
+        }
+        let shadow = bans::is_shadow_banned(UserId(uid), &state).await?;
+        match submit(&video, uid, shadow, &state).await {
+            // Пользователь в теневом бане не должен заметить разницы
+            Ok(request) if shadow => {
+                withdrawable = Some(request.id);
+                &format!("Добавлено!\n{}", status::queue_text(&request, UserId(uid), &state).await?)
             },
-            Err(err) => {
-                tracing::error!("Caused an exception in add_video due: {err:?}");
-                &format!("{err:?}")
+            Ok(request) => {
+                withdrawable = Some(request.id);
+                let mut mesg = format!("Добавленно новое видео: <b>{}</b>!", video.meta.title);
+                if let Some(note) = &video.note {
+                    mesg.push_str(&format!("\n💬 {}", html::escape(note)));
+                }
+                if video.flagged {
+                    mesg.push_str(&format!("\n⚠️ Требует проверки: /{}", request.id));
+                }
+                let place = status::queue_text(&request, UserId(uid), &state).await?;
+                // Отправляем уведомления
+                let bot_clone = bot.clone();
+                tokio::spawn(async move {
+                    let _ = notify(&bot_clone, mesg, &state, vec![UserId(uid)]).await.inspect_err(|err| {
+                        tracing::error!("Caused an exception in notify due: {err:?}");
+                    });
+                });
+                &format!("Добавлено!\n{place}")
             },
+            Err(err) => &format!("{err:?}"),
         }
     } else {
         "Отменено."
     };
+    let mut reply = bot.edit_message_text(msg.chat.id, msg.id, text);
+    if let Some(rid) = withdrawable {
+        reply = reply.reply_markup(InlineKeyboardMarkup::new(vec![vec![my::withdraw_button("Отозвать", rid, None)]]));
+    }
+    reply.await?;
+    dialogue.exit().await?;
+    Ok(())
+}
+
+/// Текст заметки к видео, после него снова спрашиваем подтверждение.
+pub async fn note_message(bot: Bot, msg: Message, (uid, mut video): (u64, Candidate), dialogue: MyDialogue) -> anyhow::Result<()> {
+    let Some(text) = msg.text() else {
+        bot.send_message(msg.chat.id, "Заметка должна быть текстом.").await?;
+        return Ok(());
+    };
+    // Команду в заметку не сохраняем, ждём текст дальше
+    if text.starts_with('/') {
+        bot.send_message(msg.chat.id, "Сейчас ожидается заметка к видео. Отправьте её текстом или нажмите «Без заметки».").await?;
+        return Ok(());
+    }
+    video.note = Some(markup::truncate(text.trim(), MAX_NOTE_LEN)).filter(|note| !note.is_empty());
+    bot.send_message(msg.chat.id, confirmation_text(&video))
+        .parse_mode(ParseMode::Html).reply_markup(confirmation_keyboard(&video)).await?;
+    dialogue.update(DialogueState::AcceptVideo { uid, video }).await?;
+    Ok(())
+}
+
+/// Пропуск заметки.
+pub async fn inline_note(bot: Bot, q: CallbackQuery, msg: Message, (uid, video): (u64, Candidate), dialogue: MyDialogue) -> anyhow::Result<()> {
+    bot.answer_callback_query(&q.id).await?;
+    bot.edit_message_text(msg.chat.id, msg.id, confirmation_text(&video))
+        .parse_mode(ParseMode::Html).reply_markup(confirmation_keyboard(&video)).await?;
+    dialogue.update(DialogueState::AcceptVideo { uid, video }).await?;
+    Ok(())
+}
+
+/// Выбор видео из результатов поиска и переход к обычному подтверждению.
+pub async fn inline_pick(
+    bot: Bot,
+    q: CallbackQuery,
+    msg: Message,
+    state: Arc<AppState>,
+    (uid, results): (u64, Vec<SearchResult>),
+    dialogue: MyDialogue
+) -> anyhow::Result<()> {
+    bot.answer_callback_query(&q.id).await?;
+    let data = q.data.ok_or(anyhow::anyhow!("Inline: Нет данных!"))?;
+    let picked = data.strip_prefix("pick ")
+        .and_then(|index| index.parse::<usize>().ok())
+        .and_then(|index| results.into_iter().nth(index));
+    let Some(result) = picked else {
+        bot.edit_message_text(msg.chat.id, msg.id, "Отменено.").await?;
+        dialogue.exit().await?;
+        return Ok(());
+    };
+    let link = VideoLink { platform: Platform::YouTube, id: result.id, start: None, end: None };
+    match prepare(link, &state).await? {
+        Ok(video) => {
+            bot.edit_message_text(msg.chat.id, msg.id, confirmation_text(&video))
+                .parse_mode(ParseMode::Html).reply_markup(confirmation_keyboard(&video)).await?;
+            dialogue.update(DialogueState::AcceptVideo { uid, video }).await?;
+        },
+        Err(reason) => {
+            bot.edit_message_text(msg.chat.id, msg.id, reason).await?;
+            dialogue.exit().await?;
+        },
+    }
+    Ok(())
+}
+
+/// Выбор видео и подтверждение при пакетном добавлении.
+pub async fn inline_batch(
+    bot: Bot,
+    q: CallbackQuery,
+    msg: Message,
+    state: Arc<AppState>,
+    (uid, mut videos): (u64, Vec<Candidate>),
+    dialogue: MyDialogue
+) -> anyhow::Result<()> {
+    bot.answer_callback_query(&q.id).await?;
+    let data = q.data.ok_or(anyhow::anyhow!("Inline: Нет данных!"))?;
+    if let Some(index) = data.strip_prefix("toggle ") {
+        let index: usize = index.parse()?;
+        if let Some(video) = videos.get_mut(index) {
+            video.selected = !video.selected;
+        }
+        bot.edit_message_reply_markup(msg.chat.id, msg.id).reply_markup(batch_keyboard(&videos)).await?;
+        dialogue.update(DialogueState::AcceptVideos { uid, videos }).await?;
+        return Ok(());
+    }
+
+    let text = if &data == "yes" {
+        if !videos.iter().any(|video| video.selected) {
+            bot.edit_message_text(msg.chat.id, msg.id, "Ничего не выбрано.").await?;
+            dialogue.exit().await?;
+            return Ok(());
+        }
+        let rights = state.check_rights(&UserId(uid)).await?;
+        if let Rights::Banned { reason, until } = &rights {
+            bot.edit_message_text(msg.chat.id, msg.id, bans::banned_text(reason, until)).await?;
+            dialogue.exit().await?;
+            return Ok(());
+        }
+        let quota = quota::for_rights(&rights);
+        let shadow = bans::is_shadow_banned(UserId(uid), &state).await?;
+        let mut report = String::from("Результат:");
+        let mut added = Vec::new();
+        let mut limit = None;
+        for video in videos.into_iter().filter(|video| video.selected) {
+            // Лимит проверяется для каждого видео, часть пачки может в него не поместиться
+            if limit.is_none() {
+                limit = quota::check(uid, quota, &state).await?.err();
+            }
+            if limit.is_some() {
+                report.push_str(&format!("\n⏳ лимит исчерпан: {}", video.meta.title));
+                continue;
+            }
+            let status = match submit(&video, uid, shadow, &state).await {
+                Ok(request) => {
+                    if video.flagged {
+                        added.push(format!("<b>{}</b> ⚠️ Требует проверки: /{}", video.meta.title, request.id));
+                    } else {
+                        added.push(format!("<b>{}</b>", video.meta.title));
+                    }
+                    "✅ добавлено"
+                },
+                Err(err) => match err.downcast_ref::<Rejection>() {
+                    Some(Rejection::Banned) => "⛔ в чёрном списке",
+                    Some(Rejection::ChannelBanned) => "⛔ канал в чёрном списке",
+                    Some(Rejection::AlreadyRequested) => "🔁 уже запрошено вами",
+                    Some(Rejection::Viewed(_)) => "👀 уже просмотрено",
+                    Some(Rejection::TooLong(_)) => "⏱ слишком длинное",
+                    Some(Rejection::TooShort(_)) => "⏱ слишком короткое",
+                    None => "❌ ошибка",
+                },
+            };
+            report.push_str(&format!("\n{status}: {}", video.meta.title));
+        }
+        if let Some(exceeded) = limit {
+            report.push_str(&format!("\n\n{exceeded}"));
+        }
+        if !added.is_empty() && !shadow {
+            let titles = added.join("\n");
+            let bot_clone = bot.clone();
+            tokio::spawn(async move {
+                let _ = notify(&bot_clone, format!("Добавлены новые видео:\n{titles}"), &state, vec![UserId(uid)]).await.inspect_err(|err| {
+                    tracing::error!("Caused an exception in notify due: {err:?}");
+                });
+            });
+        }
+        report
+    } else {
+        "Отменено.".to_string()
+    };
     bot.edit_message_text(msg.chat.id, msg.id, text).await?;
     dialogue.exit().await?;
     Ok(())
 }
 
+fn batch_keyboard(videos: &[Candidate]) -> InlineKeyboardMarkup {
+    let mut keyboard: Vec<Vec<InlineKeyboardButton>> = videos.iter().enumerate().map(|(index, video)| {
+        let mark = if video.selected { "✅" } else { "⬜" };
+        vec![InlineKeyboardButton::callback(format!("{mark} {}", markup::truncate(&video.meta.title, 48)), format!("toggle {index}"))]
+    }).collect();
+    keyboard.push(vec![
+        InlineKeyboardButton::callback("Добавить выбранные", "yes"),
+        InlineKeyboardButton::callback("Отменить", "no"),
+    ]);
+    InlineKeyboardMarkup::new(keyboard)
+}
+
+/// Полный цикл добавления видео пользователем: видео, запрос, действие и статистика.
+/// Предложения пользователя в теневом бане (`shadow`) скрываются и не попадают в статистику.
+async fn submit(video: &Candidate, uid: u64, shadow: bool, state: &AppState) -> anyhow::Result<requests::Model> {
+    let col = add_video(video.platform, &video.ytid, &video.meta, state).await.inspect_err(|err| {
+        tracing::error!("Caused an exception in add_video due: {err:?}");
+    })?;
+    // Теперь видео создано. Можно приступать к созданию "запроса" и действия
+    let request = add_action(&col, uid, video, shadow, state).await.inspect_err(|err| {
+        tracing::error!("Caused an exception in add_action due: {err:?}");
+    })?;
+    // Учитываем в лимитах
+    if let Err(err) = quota::record(uid, state).await {
+        tracing::error!("Caused an exception in quota record due: {err:?}");
+    }
+    // Обновляем данные о пользователе
+    if !shadow {
+        if let Err(err) = add_user(uid, state).await {
+            tracing::error!("Caused an exception in add_user due: {err:?}");
+        }
+    }
+    Ok(request)
+}
+
+/// "+1" к запросу из очереди: те же проверки подписки, бана, лимитов и одного голоса на человека,
+/// что и при добавлении ссылкой. Внутренняя ошибка — объяснение для пользователя.
+pub async fn upvote(bot: &Bot, rid: i32, uid: UserId, state: &AppState) -> anyhow::Result<Result<videos::Model, String>> {
+    if check_subscription(bot, &uid).await.is_none() {
+        return Ok(Err("Голосовать могут только подписчики канала!".to_string()));
+    }
+    let rights = state.check_rights(&uid).await?;
+    if let Rights::Banned { reason, until } = &rights {
+        return Ok(Err(bans::banned_text(reason, until)));
+    }
+    if let Err(exceeded) = quota::check(uid.0, quota::for_rights(&rights), state).await? {
+        return Ok(Err(exceeded.to_string()));
+    }
+    let Some(request) = requests::Entity::find_by_id(rid).one(&state.db).await?.filter(|request| !request.hidden) else {
+        return Ok(Err("Этого видео уже нет в очереди.".to_string()));
+    };
+    let video = request.find_related(videos::Entity).one(&state.db).await?
+        .ok_or(anyhow::anyhow!("Can't find video for {request:?}"))?;
+    if video.banned {
+        return Ok(Err(Rejection::Banned.to_string()));
+    }
+    if let Some(viewed_at) = request.viewed_at {
+        return Ok(Err(Rejection::Viewed(viewed_at).to_string()));
+    }
+    if 0 != request.find_related(actions::Entity).filter(actions::Column::Uid.eq(uid.0 as i64)).count(&state.db).await? {
+        return Ok(Err(Rejection::AlreadyRequested.to_string()));
+    }
+    // Голос из теневого бана сохраняется, но не учитывается
+    let shadow = bans::is_shadow_banned(uid, state).await?;
+    actions::ActiveModel {
+        rid: Set(request.id),
+        uid: Set(uid.0 as i64),
+        counted: Set(!shadow),
+        ..Default::default()
+    }.insert(&state.db).await?;
+    if !shadow {
+        if let Err(err) = add_user(uid.0, state).await {
+            tracing::error!("Caused an exception in add_user due: {err:?}");
+        }
+    }
+    if let Err(err) = quota::record(uid.0, state).await {
+        tracing::error!("Caused an exception in quota record due: {err:?}");
+    }
+    Ok(Ok(video))
+}
+
+/// Причины, по которым видео не может быть добавлено.
+#[derive(Debug)]
+enum Rejection {
+    Banned,
+    ChannelBanned,
+    AlreadyRequested,
+    Viewed(DateTime),
+    /// Длиннее допустимого, в секундах
+    TooLong(u32),
+    /// Короче допустимого, в секундах
+    TooShort(u32),
+}
+
+impl std::fmt::Display for Rejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Rejection::Banned => write!(f, "Ошибка: В чёрном списке!\nВероятнее всего был неоднократно просмотрен."),
+            Rejection::ChannelBanned => write!(f, "Ошибка: Канал в чёрном списке!\nВидео с этого канала не принимаются."),
+            Rejection::AlreadyRequested => write!(f, "Ошибка: Такой запрос уже существует!\nВы уже запрашивали данное видео ранее."),
+            Rejection::Viewed(viewed_at) => write!(f, "Ошибка: Просмотрено!\nВидео было отмечано как просмотренное {}", viewed_at.format("%Y-%m-%d %H:%M:%S")),
+            Rejection::TooLong(max) => write!(f, "Ошибка: Видео слишком длинное!\nМаксимальная длительность — {}.", format_duration(*max)),
+            Rejection::TooShort(min) => write!(f, "Ошибка: Видео слишком короткое!\nМинимальная длительность — {}.", format_duration(*min)),
+        }
+    }
+}
 
+impl std::error::Error for Rejection {}
 
-async fn add_video(ytid: &str, title: &str, state: &AppState) -> anyhow::Result<videos::Model> {
+/// Проверка длительности по MIN_DURATION и MAX_DURATION. Неизвестная длительность пропускается.
+fn check_duration(duration: Option<u32>) -> Result<(), Rejection> {
+    let Some(duration) = duration else { return Ok(()) };
+    if let Some(max) = *MAX_DURATION {
+        if duration > max {
+            return Err(Rejection::TooLong(max));
+        }
+    }
+    if let Some(min) = *MIN_DURATION {
+        if duration < min {
+            return Err(Rejection::TooShort(min));
+        }
+    }
+    Ok(())
+}
+
+async fn add_video(platform: Platform, ytid: &str, meta: &VideoMetadata, state: &AppState) -> anyhow::Result<videos::Model> {
     // Проверяем есть ли необходимость в создании столбца video
-    if let Some(video) = videos::Entity::find_by_id(ytid).one(&state.db).await? {
+    if let Some(video) = videos::Entity::find_by_id((ytid.to_string(), platform.into())).one(&state.db).await? {
         // Необходимо проверить заблокировано ли видео и создавался ли запрос для этого видео
         if video.banned {
-            anyhow::bail!("Ошибка: В чёрном списке!\nВероятнее всего был неоднократно просмотрен.")
+            return Err(Rejection::Banned.into());
+        }
+        if let Some(url) = meta.author_url.as_ref().or(video.channel_url.as_ref()) {
+            if channel::is_banned(url, state).await? {
+                return Err(Rejection::ChannelBanned.into());
+            }
+        }
+        check_duration(video.duration.map(|d| d as u32).or(meta.duration))?;
+        // Видео добавленные до появления расширенных метаданных дополняем
+        if (video.channel.is_none() && meta.author_name.is_some()) || (video.duration.is_none() && meta.duration.is_some()) {
+            let mut active = video.clone().into_active_model();
+            active.channel = Set(video.channel.or(meta.author_name.clone()));
+            active.channel_url = Set(video.channel_url.or(meta.author_url.clone()));
+            active.thumbnail = Set(video.thumbnail.or(meta.thumbnail_url.clone()));
+            active.duration = Set(video.duration.or(meta.duration.and_then(|d| i32::try_from(d).ok())));
+            return Ok(active.update(&state.db).await?);
         }
         Ok(video)
     } else {
+        if let Some(url) = &meta.author_url {
+            if channel::is_banned(url, state).await? {
+                return Err(Rejection::ChannelBanned.into());
+            }
+        }
+        check_duration(meta.duration)?;
         let new = videos::ActiveModel {
             ytid: Set(ytid.to_string()),
-            title: Set(title.to_string()),
+            title: Set(meta.title.clone()),
+            channel: Set(meta.author_name.clone()),
+            channel_url: Set(meta.author_url.clone()),
+            thumbnail: Set(meta.thumbnail_url.clone()),
+            duration: Set(meta.duration.and_then(|d| i32::try_from(d).ok())),
+            platform: Set(platform.into()),
             ..Default::default()
         };
         Ok(new.insert(&state.db).await?)
     }
 }
 
-async fn add_action(col: &videos::Model, uid: u64, state: &AppState) -> anyhow::Result<()> {
+async fn add_action(col: &videos::Model, uid: u64, video: &Candidate, shadow: bool, state: &AppState) -> anyhow::Result<requests::Model> {
     // Проверяем существует ли запрос
     let req = if let Some(req_col) = col.find_related(requests::Entity).one(&state.db).await? {
         // Запрос существует
         // Проверяем был ли уже просмотрен
-        if req_col.viewed_at.is_some() {
-            anyhow::bail!("Ошибка: Просмотрено!\nВидео было отмечано как просмотренное {}", req_col.viewed_at.unwrap().format("%Y-%m-%d %H:%M:%S"))
+        if let Some(viewed_at) = req_col.viewed_at {
+            return Err(Rejection::Viewed(viewed_at).into());
         }
         // Проверяем внёс ли этот пользователь свой "вклад" в этот запрос
         if 0 != req_col.find_related(actions::Entity).filter(actions::Column::Uid.eq(uid)).count(&state.db).await? {
             // Пользователь сделал свой "вклад", больше одного нельзя
-            anyhow::bail!("Ошибка: Такой запрос уже существует!\nВы уже запрашивали данное видео ранее.")
+            return Err(Rejection::AlreadyRequested.into());
+        }
+        // Вклад из теневого бана в видимый запрос сохраняется, но сам запрос не меняет
+        let affects = !shadow || req_col.hidden;
+        // Отрезок берём у первого, кто его указал
+        let clip = affects && req_col.start_at.is_none() && req_col.end_at.is_none() && (video.start.is_some() || video.end.is_some());
+        // Скрытый запрос становится видимым, как только его предложит обычный пользователь
+        let reveal = req_col.hidden && !shadow;
+        let flag = affects && video.flagged && !req_col.flagged;
+        if flag || clip || reveal {
+            let mut req_col = req_col.into_active_model();
+            if flag {
+                req_col.flagged = Set(true);
+            }
+            if reveal {
+                req_col.hidden = Set(false);
+            }
+            if clip {
+                req_col.start_at = Set(offset(video.start));
+                req_col.end_at = Set(offset(video.end));
+            }
+            req_col.update(&state.db).await?
+        } else {
+            req_col
         }
-        req_col
     } else {
         // Запрос не существует, создаём...
         let new_req = requests::ActiveModel {
             ytid: Set(col.ytid.clone()),
+            platform: Set(col.platform.clone()),
+            flagged: Set(video.flagged),
+            start_at: Set(offset(video.start)),
+            end_at: Set(offset(video.end)),
+            hidden: Set(shadow),
             ..Default::default()
         };
         new_req.insert(&state.db).await?
@@ -147,12 +644,14 @@ async fn add_action(col: &videos::Model, uid: u64, state: &AppState) -> anyhow::
     let new_act = actions::ActiveModel {
         rid: Set(req.id),
         uid: Set(uid as i64),
+        note: Set(video.note.clone()),
+        counted: Set(!shadow),
         ..Default::default()
     };
     
     // Обрабатываем ошибку на случай неудачи, чтобы удалить запрос без действия
     match new_act.insert(&state.db).await {
-        Ok(_) => Ok(()),
+        Ok(_) => Ok(req),
         Err(err) => {
             // Если для запроса не существует "действий", удаляем его.
             if 0 == req.find_related(actions::Entity).count(&state.db).await? {
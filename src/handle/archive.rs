@@ -1,10 +1,10 @@
 use std::{sync::Arc, vec};
 
-use sea_orm::{prelude::Expr, EntityTrait, QueryFilter, Set};
+use sea_orm::{prelude::Expr, EntityTrait, ModelTrait, QueryFilter, Set};
 use teloxide::{prelude::*, types::{InlineKeyboardButton, InlineKeyboardMarkup, ParseMode}};
 
-use crate::{AppState, InlineCommand};
-use database::{actions, requests, archived};
+use crate::{digest::{self, Outcome}, queue, AppState, InlineCommand};
+use database::{actions, requests, archived, videos};
 
 pub async fn command(bot: Bot, msg: Message) -> anyhow::Result<()> {
     let keyboard: Vec<Vec<InlineKeyboardButton>> = vec![
@@ -80,13 +80,15 @@ async fn archive(entities: Vec<(requests::Model, Vec<actions::Model>)>, state: &
         let creator = actions.iter()
             .min_by_key(|actions| actions.id)
             .ok_or(anyhow::anyhow!("Actions vector cannot be empty!"))?;
-        let contributors = actions.len().try_into()?;
+        let contributors = actions.iter().filter(|action| queue::counts(request, action)).count().try_into()?;
         let ytid = request.ytid.clone();
+        let platform = request.platform.clone();
         let viewed_at = request.viewed_at;
         let created_by = creator.uid;
         // let created_at = creator.created_at.clone(); Время архивации, а не создания запроса
         active_entities.push(archived::ActiveModel {
             ytid: Set(ytid),
+            platform: Set(platform),
             viewed_at: Set(viewed_at),
             created_by: Set(created_by),
             // created_at: Set(created_at),
@@ -108,11 +110,13 @@ async fn collect_viewed(state: &AppState) -> anyhow::Result<u32> {
     let entities: Vec<(requests::Model, Vec<actions::Model>)> = requests::Entity::find()
         .find_with_related(actions::Entity)
         .filter(Expr::col(requests::Column::ViewedAt).is_not_null())
+        .filter(Expr::col(requests::Column::Hidden).eq(false))
         .all(&state.db)
         .await?;
     let total = archive(entities, state).await?;
     requests::Entity::delete_many()
         .filter(Expr::col(requests::Column::ViewedAt).is_not_null())
+        .filter(Expr::col(requests::Column::Hidden).eq(false))
         .exec(&state.db)
         .await?;
     Ok(total)
@@ -120,13 +124,30 @@ async fn collect_viewed(state: &AppState) -> anyhow::Result<u32> {
 }
 
 async fn collect_all(state: &AppState) -> anyhow::Result<u32> {
+    // Скрытые запросы остаются в теневом списке до решения модератора
     let entities: Vec<(requests::Model, Vec<actions::Model>)> = requests::Entity::find()
         .find_with_related(actions::Entity)
+        .filter(Expr::col(requests::Column::Hidden).eq(false))
         .all(&state.db)
         .await?;
+    // Непросмотренные уходят в архив пропущенными, участников об этом предупреждаем
+    let mut skipped = Vec::new();
+    for (request, actions) in entities.iter().filter(|(request, _)| request.viewed_at.is_none()) {
+        let Some(video) = request.find_related(videos::Entity).one(&state.db).await? else { continue };
+        // Об отклонённых уже сообщили при бане
+        if !video.banned {
+            skipped.push((request.id, video.title, actions.iter().map(|action| action.uid).collect::<Vec<_>>()));
+        }
+    }
     let total = archive(entities, state).await?;
     requests::Entity::delete_many()
+        .filter(Expr::col(requests::Column::Hidden).eq(false))
         .exec(&state.db)
         .await?;
+    for (rid, title, uids) in skipped {
+        if let Err(err) = digest::enqueue_for(uids, rid, &title, Outcome::Skipped, state).await {
+            tracing::error!("Caused an exception in digest enqueue due: {err:?}");
+        }
+    }
     Ok(total)
 }
\ No newline at end of file
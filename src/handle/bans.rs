@@ -0,0 +1,256 @@
+use std::sync::Arc;
+
+use chrono::{Local, TimeDelta};
+use sea_orm::{prelude::*, sea_query::OnConflict, Order, QueryOrder, Set};
+use teloxide::{prelude::*, types::ParseMode, utils::html::{self, user_mention}};
+
+use database::{banned_users, moderators};
+use crate::AppState;
+
+/// Сообщение заблокированному пользователю в ответ на что угодно.
+pub async fn message(bot: Bot, msg: Message, (reason, until): (Option<String>, Option<DateTime>)) -> anyhow::Result<()> {
+    bot.send_message(msg.chat.id, banned_text(&reason, &until)).await?;
+    Ok(())
+}
+
+pub fn banned_text(reason: &Option<String>, until: &Option<DateTime>) -> String {
+    let mut text = match until {
+        Some(until) => format!("Вы заблокированы до {}.", until.format("%d.%m.%Y %H:%M")),
+        None => "Вы заблокированы.".to_string(),
+    };
+    if let Some(reason) = reason {
+        text.push_str(&format!("\nПричина: {reason}"));
+    }
+    text
+}
+
+/// /ban UID [причина]
+pub async fn ban_command(bot: Bot, msg: Message, id: UserId, state: Arc<AppState>, args: String) -> anyhow::Result<()> {
+    let (uid, reason) = split_arg(&args);
+    let Some(uid) = uid.and_then(|uid| uid.parse::<u64>().ok()) else {
+        bot.send_message(msg.chat.id, "После команды необходимо указать UID пользователя. (/ban 1234567 спам)").await?;
+        return Ok(());
+    };
+    let text = respond(ban(UserId(uid), reason, None, false, id, &state).await, "Пользователь заблокирован!");
+    bot.send_message(msg.chat.id, text).await?;
+    Ok(())
+}
+
+/// /shadowban UID [причина]
+pub async fn shadowban_command(bot: Bot, msg: Message, id: UserId, state: Arc<AppState>, args: String) -> anyhow::Result<()> {
+    let (uid, reason) = split_arg(&args);
+    let Some(uid) = uid.and_then(|uid| uid.parse::<u64>().ok()) else {
+        bot.send_message(msg.chat.id, "После команды необходимо указать UID пользователя. (/shadowban 1234567 спам)").await?;
+        return Ok(());
+    };
+    let text = respond(
+        ban(UserId(uid), reason, None, true, id, &state).await,
+        "Пользователь в теневом бане! Его видео будут видны только в /shadow.",
+    );
+    bot.send_message(msg.chat.id, text).await?;
+    Ok(())
+}
+
+/// /tempban UID срок [причина]
+pub async fn tempban_command(bot: Bot, msg: Message, id: UserId, state: Arc<AppState>, args: String) -> anyhow::Result<()> {
+    let (uid, rest) = split_arg(&args);
+    let (duration, reason) = split_arg(rest.as_deref().unwrap_or_default());
+    let uid = uid.and_then(|uid| uid.parse::<u64>().ok());
+    let duration = duration.and_then(parse_ban_duration);
+    let (Some(uid), Some(duration)) = (uid, duration) else {
+        bot.send_message(msg.chat.id, "Необходимо указать UID пользователя и срок: 30m, 12h, 7d или 1d12h. (/tempban 1234567 7d спам)").await?;
+        return Ok(());
+    };
+    let Some(until) = Local::now().naive_local().checked_add_signed(duration) else {
+        bot.send_message(msg.chat.id, "Слишком большой срок блокировки!").await?;
+        return Ok(());
+    };
+    let text = respond(
+        ban(UserId(uid), reason, Some(until), false, id, &state).await,
+        &format!("Пользователь заблокирован до {}!", until.format("%d.%m.%Y %H:%M")),
+    );
+    bot.send_message(msg.chat.id, text).await?;
+    Ok(())
+}
+
+pub async fn unban_command(bot: Bot, msg: Message, state: Arc<AppState>, uid: String) -> anyhow::Result<()> {
+    let text = match uid.trim().parse::<u64>() {
+        Ok(uid) => match unban(UserId(uid), &state).await {
+            Ok(true) => "Пользователь разблокирован!",
+            Ok(false) => "Этот пользователь не заблокирован.",
+            Err(err) => {
+                tracing::error!("Caused an exception in unban due: {err:?}");
+                "Произошла ошибка!"
+            },
+        },
+        Err(_) => "После команды необходимо указать UID пользователя. (/unban 1234567)",
+    };
+    bot.send_message(msg.chat.id, text).await?;
+    Ok(())
+}
+
+pub async fn list_command(bot: Bot, msg: Message, state: Arc<AppState>) -> anyhow::Result<()> {
+    let bans = banned_users::Entity::find()
+        .order_by(banned_users::Column::CreatedAt, Order::Asc)
+        .all(&state.db).await?;
+    let now = Local::now().naive_local();
+    let bans: Vec<_> = bans.into_iter().filter(|ban| ban.expires_at.is_none_or(|until| until > now)).collect();
+    let text = if bans.is_empty() {
+        "Заблокированных пользователей нет.".to_string()
+    } else {
+        let mut text = String::from("Заблокированные пользователи:");
+        for ban in bans {
+            let until = match ban.expires_at {
+                Some(until) => format!("до {}", until.format("%d.%m.%Y %H:%M")),
+                None if ban.shadow => "👻 теневой".to_string(),
+                None => "навсегда".to_string(),
+            };
+            text.push_str(&format!(
+                "\n - {} {until}, UID модератора: {}",
+                user_mention(UserId(ban.id as u64), &ban.id.to_string()), ban.banned_by
+            ));
+            if let Some(reason) = ban.reason {
+                text.push_str(&format!("\nПричина: {}", html::escape(&reason)));
+            }
+        }
+        text
+    };
+    bot.send_message(msg.chat.id, text).parse_mode(ParseMode::Html).await?;
+    Ok(())
+}
+
+//
+// Auxiliary functions
+//
+
+/// Почему пользователя нельзя заблокировать.
+#[derive(Debug)]
+pub enum BanError {
+    Moderator,
+}
+
+impl std::fmt::Display for BanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BanError::Moderator => write!(f, "Нельзя заблокировать модератора!"),
+        }
+    }
+}
+
+impl std::error::Error for BanError {}
+
+fn respond(result: anyhow::Result<()>, success: &str) -> String {
+    match result {
+        Ok(()) => success.to_string(),
+        Err(err) => match err.downcast_ref::<BanError>() {
+            Some(reason) => reason.to_string(),
+            None => {
+                tracing::error!("Caused an exception in ban due: {err:?}");
+                "Произошла ошибка!".to_string()
+            },
+        },
+    }
+}
+
+/// Блокирует пользователя или обновляет существующую блокировку.
+/// При `shadow` пользователь о блокировке не узнаёт, а его видео скрываются.
+pub async fn ban(uid: UserId, reason: Option<String>, until: Option<DateTime>, shadow: bool, by: UserId, state: &AppState) -> anyhow::Result<()> {
+    if moderators::Entity::find_by_id(uid.0 as i64).one(&state.db).await?.is_some() {
+        return Err(BanError::Moderator.into());
+    }
+    banned_users::Entity::insert(banned_users::ActiveModel {
+        id: Set(uid.0 as i64),
+        reason: Set(reason),
+        banned_by: Set(by.0 as i64),
+        created_at: Set(Local::now().naive_local()),
+        expires_at: Set(until),
+        shadow: Set(shadow),
+    }).on_conflict(OnConflict::column(banned_users::Column::Id)
+        .update_columns([
+            banned_users::Column::Reason,
+            banned_users::Column::BannedBy,
+            banned_users::Column::CreatedAt,
+            banned_users::Column::ExpiresAt,
+            banned_users::Column::Shadow,
+        ]).to_owned()
+    ).exec(&state.db).await?;
+    Ok(())
+}
+
+/// Возвращает `false` если пользователь не был заблокирован.
+pub async fn unban(uid: UserId, state: &AppState) -> anyhow::Result<bool> {
+    Ok(banned_users::Entity::delete_by_id(uid.0 as i64).exec(&state.db).await?.rows_affected != 0)
+}
+
+/// Действующая блокировка пользователя.
+pub async fn find_active(uid: UserId, state: &AppState) -> anyhow::Result<Option<banned_users::Model>> {
+    let ban = banned_users::Entity::find_by_id(uid.0 as i64).one(&state.db).await?;
+    let now = Local::now().naive_local();
+    Ok(ban.filter(|ban| ban.expires_at.is_none_or(|until| until > now)))
+}
+
+pub async fn is_shadow_banned(uid: UserId, state: &AppState) -> anyhow::Result<bool> {
+    Ok(find_active(uid, state).await?.is_some_and(|ban| ban.shadow))
+}
+
+/// Первое слово и остаток строки.
+fn split_arg(args: &str) -> (Option<&str>, Option<String>) {
+    let mut parts = args.trim().splitn(2, char::is_whitespace);
+    let first = parts.next().filter(|first| !first.is_empty());
+    let rest = parts.next().map(str::trim).filter(|rest| !rest.is_empty()).map(str::to_string);
+    (first, rest)
+}
+
+/// Разбирает срок блокировки: `30m`, `12h`, `7d`, `1d12h`.
+fn parse_ban_duration(value: &str) -> Option<TimeDelta> {
+    let mut total = TimeDelta::zero();
+    let mut number = String::new();
+    for c in value.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let n: i64 = number.parse().ok()?;
+        number.clear();
+        total = total.checked_add(&match c {
+            'd' => TimeDelta::try_days(n)?,
+            'h' => TimeDelta::try_hours(n)?,
+            'm' => TimeDelta::try_minutes(n)?,
+            _ => return None,
+        })?;
+    }
+    (number.is_empty() && total > TimeDelta::zero()).then_some(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ban_duration() {
+        assert_eq!(parse_ban_duration("30m"), Some(TimeDelta::minutes(30)));
+        assert_eq!(parse_ban_duration("12h"), Some(TimeDelta::hours(12)));
+        assert_eq!(parse_ban_duration("7d"), Some(TimeDelta::days(7)));
+        assert_eq!(parse_ban_duration("1d12h"), Some(TimeDelta::hours(36)));
+        assert_eq!(parse_ban_duration("0d"), None);
+        assert_eq!(parse_ban_duration("7"), None);
+        assert_eq!(parse_ban_duration("7w"), None);
+        assert_eq!(parse_ban_duration(""), None);
+    }
+
+    #[test]
+    fn test_parse_ban_duration_overflow() {
+        assert_eq!(parse_ban_duration("99999999999999999999d"), None);
+        assert_eq!(parse_ban_duration("60000000000d60000000000d"), None);
+        // Срок разбирается, но дата окончания за пределами NaiveDateTime
+        let duration = parse_ban_duration("99999999d").unwrap();
+        assert_eq!(Local::now().naive_local().checked_add_signed(duration), None);
+    }
+
+    #[test]
+    fn test_split_arg() {
+        assert_eq!(split_arg(" 123  спам и флуд "), (Some("123"), Some("спам и флуд".to_string())));
+        assert_eq!(split_arg("123"), (Some("123"), None));
+        assert_eq!(split_arg("  "), (None, None));
+    }
+}
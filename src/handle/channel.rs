@@ -0,0 +1,104 @@
+use std::sync::Arc;
+
+use sea_orm::{prelude::*, ActiveModelTrait, Order, QueryOrder, Set};
+use teloxide::{prelude::*, types::{LinkPreviewOptions, ParseMode}, utils::html};
+
+use database::banned_channels;
+use crate::AppState;
+
+pub async fn ban_command(bot: Bot, msg: Message, id: UserId, state: Arc<AppState>, url: String) -> anyhow::Result<()> {
+    let text = if let Some(url) = state.metadata.channel_url(&url).await {
+        match ban(&url, None, id, &state).await {
+            Ok(true) => "Канал добавлен в чёрный список!",
+            Ok(false) => "Канал уже в чёрном списке.",
+            Err(err) => {
+                tracing::error!("Caused an exception in ban channel due: {err:?}");
+                "Произошла ошибка!"
+            },
+        }
+    } else {
+        "После команды необходимо указать ссылку на YouTube или Rutube канал. (/banchannel https://www.youtube.com/@channel)"
+    };
+    bot.send_message(msg.chat.id, text).await?;
+    Ok(())
+}
+
+pub async fn unban_command(bot: Bot, msg: Message, state: Arc<AppState>, url: String) -> anyhow::Result<()> {
+    let text = if let Some(url) = state.metadata.channel_url(&url).await {
+        match unban(&url, &state).await {
+            Ok(true) => "Канал убран из чёрного списка!",
+            Ok(false) => "Такого канала нет в чёрном списке.",
+            Err(err) => {
+                tracing::error!("Caused an exception in unban channel due: {err:?}");
+                "Произошла ошибка!"
+            },
+        }
+    } else {
+        "После команды необходимо указать ссылку на YouTube или Rutube канал. (/unbanchannel https://www.youtube.com/@channel)"
+    };
+    bot.send_message(msg.chat.id, text).await?;
+    Ok(())
+}
+
+pub async fn list_command(bot: Bot, msg: Message, state: Arc<AppState>) -> anyhow::Result<()> {
+    let channels = banned_channels::Entity::find()
+        .order_by(banned_channels::Column::CreatedAt, Order::Asc)
+        .all(&state.db).await?;
+    let text = if channels.is_empty() {
+        "Чёрный список каналов пуст.".to_string()
+    } else {
+        let mut text = String::from("Каналы в чёрном списке:");
+        for channel in channels {
+            let name = html::escape(channel.name.as_deref().unwrap_or(&channel.url));
+            text.push_str(&format!(
+                "\n - <a href=\"{}\">{name}</a>\nС {}, UID модератора: {}",
+                channel.url, channel.created_at.format("%Y-%m-%d %H:%M:%S"), channel.banned_by
+            ));
+        }
+        text
+    };
+    bot.send_message(msg.chat.id, text).parse_mode(ParseMode::Html)
+        .link_preview_options(LinkPreviewOptions {
+            is_disabled: true,
+            url: None,
+            prefer_small_media: false,
+            prefer_large_media: false,
+            show_above_text: false
+        }).await?;
+    Ok(())
+}
+
+//
+// Auxiliary functions
+//
+
+/// Возвращает `false` если канал уже был заблокирован.
+pub async fn ban(url: &str, name: Option<String>, by: UserId, state: &AppState) -> anyhow::Result<bool> {
+    if banned_channels::Entity::find_by_id(url).one(&state.db).await?.is_some() {
+        return Ok(false);
+    }
+    banned_channels::ActiveModel {
+        url: Set(url.to_string()),
+        name: Set(name),
+        banned_by: Set(by.0 as i64),
+        ..Default::default()
+    }.insert(&state.db).await?;
+    Ok(true)
+}
+
+/// Возвращает `false` если канала не было в чёрном списке.
+/// `url` — каноническая ссылка, см. [`MetadataCache::channel_url`](crate::metadata::MetadataCache::channel_url).
+pub async fn unban(url: &str, state: &AppState) -> anyhow::Result<bool> {
+    Ok(banned_channels::Entity::delete_by_id(url).exec(&state.db).await?.rows_affected != 0)
+}
+
+/// Проверяет ссылку на канал в любом виде по чёрному списку.
+pub async fn is_banned(url: &str, state: &AppState) -> anyhow::Result<bool> {
+    let (Some(normalized), Some(canonical)) = (youtube::normalize_channel_url(url), state.metadata.channel_url(url).await) else {
+        return Ok(false);
+    };
+    // Баны, добавленные до приведения ссылок к ID, хранятся в нормализованном виде
+    Ok(banned_channels::Entity::find()
+        .filter(banned_channels::Column::Url.is_in([canonical, normalized]))
+        .one(&state.db).await?.is_some())
+}
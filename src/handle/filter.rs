@@ -0,0 +1,232 @@
+use std::sync::Arc;
+
+use regex::{Regex, RegexBuilder};
+use sea_orm::{prelude::*, ActiveModelTrait, IntoActiveModel, Order, QueryOrder, Set};
+use teloxide::{prelude::*, types::ParseMode, utils::html};
+use youtube::{Platform, VideoMetadata};
+
+use database::{filters, videos, sea_orm_active_enums::{FilterAction, FilterKind}};
+use crate::AppState;
+
+const USAGE: &str = "Использование:\n\
+    /filter — список правил\n\
+    /filter add &lt;keyword|regex&gt; &lt;reject|flag|ban&gt; &lt;шаблон&gt;\n\
+    /filter rem &lt;id&gt;\n\
+    /filter test &lt;название&gt;";
+
+/// Решение фильтров по названию видео.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Pass,
+    Flag,
+    Reject,
+    Ban,
+}
+
+pub async fn command(bot: Bot, msg: Message, id: UserId, state: Arc<AppState>, args: String) -> anyhow::Result<()> {
+    let args = args.trim();
+    let (subcommand, rest) = args.split_once(char::is_whitespace)
+        .map(|(subcommand, rest)| (subcommand, rest.trim()))
+        .unwrap_or((args, ""));
+    let text = match subcommand {
+        "" | "list" => list(&state).await?,
+        "add" => add(rest, id, &state).await?,
+        "rem" | "remove" => remove(rest, &state).await?,
+        "test" => test(rest, &state).await?,
+        _ => USAGE.to_string(),
+    };
+    bot.send_message(msg.chat.id, text).parse_mode(ParseMode::Html).await?;
+    Ok(())
+}
+
+async fn list(state: &AppState) -> anyhow::Result<String> {
+    let rules = filters::Entity::find().order_by(filters::Column::Id, Order::Asc).all(&state.db).await?;
+    if rules.is_empty() {
+        return Ok(format!("Правил нет.\n\n{USAGE}"));
+    }
+    let mut text = String::from("Правила фильтрации названий:");
+    for rule in rules {
+        text.push_str(&format!("\n{}", describe(&rule)));
+    }
+    Ok(text)
+}
+
+async fn add(args: &str, id: UserId, state: &AppState) -> anyhow::Result<String> {
+    let mut parts = args.splitn(3, char::is_whitespace);
+    let kind = match parts.next() {
+        Some("keyword") => FilterKind::Keyword,
+        Some("regex") => FilterKind::Regex,
+        _ => return Ok(USAGE.to_string()),
+    };
+    let action = match parts.next() {
+        Some("reject") => FilterAction::Reject,
+        Some("flag") => FilterAction::Flag,
+        Some("ban") => FilterAction::Ban,
+        _ => return Ok(USAGE.to_string()),
+    };
+    let pattern = match parts.next().map(str::trim) {
+        Some(pattern) if !pattern.is_empty() => pattern.to_string(),
+        _ => return Ok(USAGE.to_string()),
+    };
+    if kind == FilterKind::Regex {
+        if let Err(err) = build_regex(&pattern) {
+            return Ok(format!("Ошибка в регулярном выражении:\n<code>{}</code>", html::escape(&err.to_string())));
+        }
+    }
+    let rule = filters::ActiveModel {
+        kind: Set(kind),
+        pattern: Set(pattern),
+        action: Set(action),
+        created_by: Set(id.0 as i64),
+        ..Default::default()
+    }.insert(&state.db).await?;
+    Ok(format!("Правило добавлено:\n{}", describe(&rule)))
+}
+
+async fn remove(args: &str, state: &AppState) -> anyhow::Result<String> {
+    let Ok(id) = args.parse::<i32>() else {
+        return Ok("После команды необходимо указать номер правила. (/filter rem 1)".to_string());
+    };
+    Ok(if filters::Entity::delete_by_id(id).exec(&state.db).await?.rows_affected != 0 {
+        "Правило удалено!".to_string()
+    } else {
+        "Такого правила не существует.".to_string()
+    })
+}
+
+async fn test(title: &str, state: &AppState) -> anyhow::Result<String> {
+    if title.is_empty() {
+        return Ok("После команды необходимо указать название видео. (/filter test Название)".to_string());
+    }
+    let rules = filters::Entity::find().all(&state.db).await?;
+    let matched: Vec<&filters::Model> = rules.iter().filter(|rule| matches(rule, title)).collect();
+    if matched.is_empty() {
+        return Ok("Ни одно правило не сработало.".to_string());
+    }
+    let mut text = String::from("Сработали правила:");
+    for rule in &matched {
+        text.push_str(&format!("\n{}", describe(rule)));
+    }
+    if let Some(rule) = strictest(&rules, title) {
+        text.push_str(&format!("\n\nИтог: <b>{}</b>", action_name(&rule.action)));
+    }
+    Ok(text)
+}
+
+//
+// Auxiliary functions
+//
+
+/// Проверяет название по правилам. При срабатывании "ban" видео сразу попадает в чёрный список.
+pub async fn screen(platform: Platform, ytid: &str, meta: &VideoMetadata, state: &AppState) -> anyhow::Result<Verdict> {
+    let rules = filters::Entity::find().all(&state.db).await?;
+    Ok(match strictest(&rules, &meta.title).map(|rule| &rule.action) {
+        None => Verdict::Pass,
+        Some(FilterAction::Flag) => Verdict::Flag,
+        Some(FilterAction::Reject) => Verdict::Reject,
+        Some(FilterAction::Ban) => {
+            ban_video(platform, ytid, meta, state).await?;
+            Verdict::Ban
+        },
+    })
+}
+
+async fn ban_video(platform: Platform, ytid: &str, meta: &VideoMetadata, state: &AppState) -> anyhow::Result<()> {
+    if let Some(video) = videos::Entity::find_by_id((ytid.to_string(), platform.into())).one(&state.db).await? {
+        let mut video = video.into_active_model();
+        video.banned = Set(true);
+        video.update(&state.db).await?;
+    } else {
+        videos::ActiveModel {
+            ytid: Set(ytid.to_string()),
+            title: Set(meta.title.clone()),
+            banned: Set(true),
+            channel: Set(meta.author_name.clone()),
+            channel_url: Set(meta.author_url.clone()),
+            thumbnail: Set(meta.thumbnail_url.clone()),
+            duration: Set(meta.duration.and_then(|d| i32::try_from(d).ok())),
+            platform: Set(platform.into()),
+        }.insert(&state.db).await?;
+    }
+    Ok(())
+}
+
+fn build_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(pattern).case_insensitive(true).size_limit(1 << 20).build()
+}
+
+fn matches(rule: &filters::Model, title: &str) -> bool {
+    match rule.kind {
+        FilterKind::Keyword => title.to_lowercase().contains(&rule.pattern.to_lowercase()),
+        FilterKind::Regex => match build_regex(&rule.pattern) {
+            Ok(regex) => regex.is_match(title),
+            Err(err) => {
+                tracing::warn!("Invalid regex in filter #{}: {err}", rule.id);
+                false
+            },
+        },
+    }
+}
+
+/// Самое строгое из сработавших правил.
+fn strictest<'a>(rules: &'a [filters::Model], title: &str) -> Option<&'a filters::Model> {
+    rules.iter()
+        .filter(|rule| matches(rule, title))
+        .max_by_key(|rule| match rule.action {
+            FilterAction::Flag => 0,
+            FilterAction::Reject => 1,
+            FilterAction::Ban => 2,
+        })
+}
+
+fn action_name(action: &FilterAction) -> &'static str {
+    match action {
+        FilterAction::Reject => "отклонить",
+        FilterAction::Flag => "на проверку",
+        FilterAction::Ban => "в бан",
+    }
+}
+
+fn describe(rule: &filters::Model) -> String {
+    let kind = match rule.kind {
+        FilterKind::Keyword => "слово",
+        FilterKind::Regex => "regex",
+    };
+    format!("#{} [{kind}] {}: <code>{}</code>", rule.id, action_name(&rule.action), html::escape(&rule.pattern))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(id: i32, kind: FilterKind, pattern: &str, action: FilterAction) -> filters::Model {
+        filters::Model { id, kind, pattern: pattern.to_string(), action, created_by: 0, created_at: Default::default() }
+    }
+
+    #[test]
+    fn test_matches() {
+        let keyword = rule(1, FilterKind::Keyword, "Реакция", FilterAction::Reject);
+        assert!(matches(&keyword, "РЕАКЦИЯ на видео"));
+        assert!(!matches(&keyword, "Обзор"));
+
+        let regex = rule(2, FilterKind::Regex, r"^\[\d+\]", FilterAction::Flag);
+        assert!(matches(&regex, "[18] Видео"));
+        assert!(!matches(&regex, "Видео [18]"));
+
+        let invalid = rule(3, FilterKind::Regex, "(", FilterAction::Ban);
+        assert!(!matches(&invalid, "("));
+    }
+
+    #[test]
+    fn test_strictest() {
+        let rules = vec![
+            rule(1, FilterKind::Keyword, "стрим", FilterAction::Flag),
+            rule(2, FilterKind::Keyword, "казино", FilterAction::Ban),
+            rule(3, FilterKind::Regex, "каз.но", FilterAction::Reject),
+        ];
+        assert_eq!(strictest(&rules, "Обычное видео"), None);
+        assert_eq!(strictest(&rules, "Стрим").map(|rule| rule.id), Some(1));
+        assert_eq!(strictest(&rules, "Стрим в казино").map(|rule| rule.id), Some(2));
+        assert_eq!(strictest(&rules, "Стрим в казёно").map(|rule| rule.id), Some(3));
+    }
+}
@@ -1,38 +1,106 @@
 use std::sync::Arc;
 
 use chrono::Local;
-use teloxide::{prelude::*, types::{InlineKeyboardButton, InlineKeyboardMarkup, ParseMode}, utils::html::user_mention};
+use teloxide::{prelude::*, types::{InlineKeyboardButton, InlineKeyboardMarkup, ParseMode}, utils::html::{self, user_mention}};
 use sea_orm::{prelude::*, IntoActiveModel, Order, QueryOrder as _, Set};
 
-use crate::{AppState, InlineCommand};
-use database::*;
-use youtube::DEFAULT_YT;
+use crate::{digest::{self, Outcome}, markup, queue, AppState, InlineCommand, Rights};
+use super::{bans, channel, status};
+use database::{*, sea_orm_active_enums::Status};
+use youtube::{format_duration, Platform};
 
 // Вытаскивает VID из сообщений: /123 или 123
 pub fn recognise_vid(text: &str) -> Option<i32> {
     if let Ok(vid) = text.parse::<i32>() {
         Some(vid)
     } else if let Some(unslash) = text.strip_prefix("/") {
-        if let Ok(vid) = unslash.parse::<i32>() {
-            Some(vid)
-        } else {
-            None
-        }
+        unslash.parse::<i32>().ok()
     } else {
         None
     }
 }
 
-pub async fn message(bot: Bot, msg: Message, state: Arc<AppState>, rid: i32) -> anyhow::Result<()> {
-    match collect_info(&rid, &state).await {
+pub async fn message(bot: Bot, msg: Message, uid: UserId, rights: Rights, state: Arc<AppState>, rid: i32) -> anyhow::Result<()> {
+    let moderator = matches!(rights, Rights::Moderator { .. });
+    let info = match collect_info(&rid, &state).await {
+        // Скрытый запрос видят только модераторы и те, кто его предложил
+        Ok((_, request, ..)) if request.hidden && !moderator && !status::contributed(&request, uid, &state).await? => {
+            Err(anyhow::anyhow!("Can't find request ID {rid}"))
+        },
+        info => info,
+    };
+    match info {
         Ok((video, request, creator, contributors)) => {
             let name = bot.get_chat_member(ChatId(creator.uid), UserId(creator.uid as u64)).await?.user.full_name();
             let creator_mention = user_mention(UserId(creator.uid as u64), &name);
 
-            let out: String = format!(
-                "<a href=\"{DEFAULT_YT}{}\">{}</a>\n\
-                Добавлено {creator_mention} (👀{contributors})"
-                , video.ytid, video.title);
+            let platform = Platform::from(video.platform.clone());
+            let url = platform.video_url(&video.ytid, request.start_at.map(|s| s as u32));
+            let mut out: String = format!("<a href=\"{url}\">{}</a>", video.title);
+            if let Some(clip) = markup::clip_label(request.start_at, request.end_at) {
+                out.push_str(&format!("\nОтрезок: {clip}"));
+            }
+            if let Some(channel) = &video.channel {
+                let channel = html::escape(channel);
+                if let Some(channel_url) = &video.channel_url {
+                    out.push_str(&format!("\nКанал: <a href=\"{}\">{channel}</a>", html::escape(channel_url)));
+                } else {
+                    out.push_str(&format!("\nКанал: {channel}"));
+                }
+            }
+            if let Some(duration) = video.duration {
+                out.push_str(&format!("\nДлительность: {}", format_duration(duration as u32)));
+            }
+            if let Some(thumbnail) = &video.thumbnail {
+                out.push_str(&format!("\n<a href=\"{thumbnail}\">Превью</a>"));
+            }
+            out.push_str(&format!("\nДобавлено {creator_mention} (👀{contributors})"));
+            let notes = request.find_related(actions::Entity)
+                .filter(actions::Column::Note.is_not_null())
+                .order_by(actions::Column::Id, Order::Asc)
+                .all(&state.db).await?;
+            // Заметки из теневого бана видны только модераторам и самому автору
+            let notes: Vec<_> = notes.into_iter()
+                .filter(|action| moderator || queue::counts(&request, action) || action.uid == uid.0 as i64)
+                .collect();
+            if !notes.is_empty() {
+                out.push_str("\nЗаметки:");
+                for action in notes {
+                    let note = action.note.unwrap_or_default();
+                    out.push_str(&format!("\n💬 {}: {}", user_mention(UserId(action.uid as u64), &action.uid.to_string()), html::escape(&note)));
+                }
+            }
+            if request.status == Status::Unavailable {
+                out.push_str("\n🚫 Видео стало недоступно!");
+            }
+            // Дальше пометки и кнопки модератора
+            if !moderator {
+                bot.send_message(msg.chat.id, out).parse_mode(ParseMode::Html).await?;
+                return Ok(());
+            }
+            if request.flagged {
+                out.push_str("\n⚠️ Помечено фильтром, требует проверки!");
+            }
+            // Ошибка проверки канала не должна мешать показать карточку
+            let channel_banned = match &video.channel_url {
+                Some(url) => Some(channel::is_banned(url, &state).await.unwrap_or_else(|err| {
+                    tracing::error!("Caused an exception in channel is_banned due: {err:?}");
+                    false
+                })),
+                None => None,
+            };
+            if channel_banned == Some(true) {
+                out.push_str("\n⛔ Канал в чёрном списке!");
+            }
+            let creator_ban = bans::find_active(UserId(creator.uid as u64), &state).await?;
+            match &creator_ban {
+                Some(ban) if ban.shadow => out.push_str("\n👻 Автор в теневом бане!"),
+                Some(_) => out.push_str("\n⛔ Автор заблокирован!"),
+                None => (),
+            }
+            if request.hidden {
+                out.push_str("\n👻 Скрыто из очереди, см. /shadow");
+            }
 
             // TODO: УБЕДИТСЯ ЧТО НЕ ТРЕБУЕТСЯ https://docs.rs/teloxide/latest/teloxide/types/struct.LinkPreviewOptions.html
             let ban_title = if video.banned {
@@ -45,12 +113,35 @@ pub async fn message(bot: Bot, msg: Message, state: Arc<AppState>, rid: i32) ->
             } else {
                 ("В просмотренные", "view")
             };
-            let keyboard: Vec<Vec<InlineKeyboardButton>> = vec![
+            let mut keyboard: Vec<Vec<InlineKeyboardButton>> = vec![
                 vec![
                     InlineKeyboardButton::callback(viewed_title.0, format!("{} {}", viewed_title.1, request.id)),
                     InlineKeyboardButton::callback(ban_title.0, format!("{} {}", ban_title.1, request.id))
                 ]
             ];
+            if request.flagged {
+                keyboard.push(vec![InlineKeyboardButton::callback("Одобрить", format!("approve {}", request.id))]);
+            }
+            if request.hidden {
+                keyboard.push(vec![
+                    InlineKeyboardButton::callback("Показать в очереди", format!("promote {}", request.id)),
+                    InlineKeyboardButton::callback("Удалить", format!("purge {}", request.id)),
+                ]);
+            }
+            if let Some(banned) = channel_banned {
+                let channel_title = if banned {
+                    ("Разбанить канал", "pardon_channel")
+                } else {
+                    ("Канал в бан", "ban_channel")
+                };
+                keyboard.push(vec![InlineKeyboardButton::callback(channel_title.0, format!("{} {}", channel_title.1, request.id))]);
+            }
+            let user_title = if creator_ban.is_some() {
+                ("Разбанить автора", "unban_user")
+            } else {
+                ("Забанить автора", "ban_user")
+            };
+            keyboard.push(vec![InlineKeyboardButton::callback(user_title.0, format!("{} {}", user_title.1, request.id))]);
             bot.send_message(msg.chat.id, out).parse_mode(ParseMode::Html).reply_markup(InlineKeyboardMarkup::new(keyboard)).await?;
         },
         Err(err) => {
@@ -73,7 +164,9 @@ async fn collect_info(rid: &i32, state: &AppState) -> anyhow::Result<(videos::Mo
         .one(&state.db).await?
         .ok_or(anyhow::anyhow!("Can't find creator entry for {request:?}"))?;
 
-    let contributors = request.find_related(actions::Entity).count(&state.db).await?;
+    let contributors = request.find_related(actions::Entity).all(&state.db).await?
+        .iter().filter(|action| queue::counts(&request, action))
+        .count() as u64;
 
     Ok((video, request, creator, contributors))
 }
@@ -128,6 +221,89 @@ pub async fn inline(bot: Bot, q: CallbackQuery, chatid: ChatId, state: Arc<AppSt
                     },
                 }
             },
+            InlineCommand::BanChannel(rid) => {
+                match ban_channel(&rid, q.from.id, &state).await {
+                    Ok(Some(vid)) => {
+                        &format!("Канал видео <b>\"{}\"</b> добавлен в чёрный список!", vid.title)
+                    },
+                    Ok(None) => "Канал уже в чёрном списке.",
+                    Err(err) => {
+                        tracing::error!("Caused an exception in ban_channel due: {err:?}");
+                        &format!("{err:?}")
+                    },
+                }
+            },
+            InlineCommand::PardonChannel(rid) => {
+                match pardon_channel(&rid, &state).await {
+                    Ok(Some(vid)) => {
+                        &format!("Канал видео <b>\"{}\"</b> убран из чёрного списка!", vid.title)
+                    },
+                    Ok(None) => "Канала нет в чёрном списке.",
+                    Err(err) => {
+                        tracing::error!("Caused an exception in pardon_channel due: {err:?}");
+                        &format!("{err:?}")
+                    },
+                }
+            },
+            InlineCommand::Approve(rid) => {
+                match approve(&rid, &state).await {
+                    Ok(vid) => {
+                        &format!("Видео <b>\"{}\"</b> одобрено!", vid.title)
+                    },
+                    Err(err) => {
+                        tracing::error!("Caused an exception in approve due: {err:?}");
+                        &format!("{err:?}")
+                    },
+                }
+            },
+            InlineCommand::BanUser(rid) => {
+                match ban_user(&rid, q.from.id, &state).await {
+                    Ok(uid) => {
+                        &format!("Пользователь {} заблокирован!", user_mention(uid, &uid.to_string()))
+                    },
+                    Err(err) => match err.downcast_ref::<bans::BanError>() {
+                        Some(reason) => &reason.to_string(),
+                        None => {
+                            tracing::error!("Caused an exception in ban_user due: {err:?}");
+                            &format!("{err:?}")
+                        },
+                    },
+                }
+            },
+            InlineCommand::UnbanUser(rid) => {
+                match unban_user(&rid, &state).await {
+                    Ok(Some(uid)) => {
+                        &format!("Пользователь {} разблокирован!", user_mention(uid, &uid.to_string()))
+                    },
+                    Ok(None) => "Автор не заблокирован.",
+                    Err(err) => {
+                        tracing::error!("Caused an exception in unban_user due: {err:?}");
+                        &format!("{err:?}")
+                    },
+                }
+            },
+            InlineCommand::Promote(rid) => {
+                match promote(&rid, &state).await {
+                    Ok(vid) => {
+                        &format!("Видео <b>\"{}\"</b> добавлено в очередь!", vid.title)
+                    },
+                    Err(err) => {
+                        tracing::error!("Caused an exception in promote due: {err:?}");
+                        &format!("{err:?}")
+                    },
+                }
+            },
+            InlineCommand::Purge(rid) => {
+                match purge(&rid, &state).await {
+                    Ok(vid) => {
+                        &format!("Скрытый запрос <b>\"{}\"</b> удалён!", vid.title)
+                    },
+                    Err(err) => {
+                        tracing::error!("Caused an exception in purge due: {err:?}");
+                        &format!("{err:?}")
+                    },
+                }
+            },
             _ => {
                 tracing::error!("Unrecognized status! {command:?}");
                 "Ошибка распознавания!"
@@ -146,15 +322,33 @@ async fn ban(rid: &i32, state: &AppState) -> anyhow::Result<videos::Model> {
     let mut video = request.find_related(videos::Entity).one(&state.db).await?
         .ok_or(anyhow::anyhow!("Can't find video for {request:?}"))?.into_active_model();
     video.banned = Set(true);
-    Ok(video.update(&state.db).await?)
+    let video = video.update(&state.db).await?;
+    if request.viewed_at.is_none() && !request.hidden {
+        digest::enqueue_logged(request.id, &video.title, Outcome::Rejected, state).await;
+    }
+    Ok(video)
 }
 
 async fn view(rid: &i32, state: &AppState) -> anyhow::Result<videos::Model> {
     let mut request = requests::Entity::find_by_id(*rid).one(&state.db).await?
-    .ok_or(anyhow::anyhow!("Can't find request ID {rid}"))?.into_active_model();
-request.viewed_at = Set(Some(Local::now().naive_local()));
-request.update(&state.db).await?.find_related(videos::Entity).one(&state.db).await?
-.ok_or(anyhow::anyhow!("Can't find video by RID {rid}"))
+        .ok_or(anyhow::anyhow!("Can't find request ID {rid}"))?.into_active_model();
+    request.viewed_at = Set(Some(Local::now().naive_local()));
+    let request = request.update(&state.db).await?;
+    let video = request.find_related(videos::Entity).one(&state.db).await?
+        .ok_or(anyhow::anyhow!("Can't find video by RID {rid}"))?;
+    if !request.hidden {
+        digest::enqueue_logged(request.id, &video.title, Outcome::Viewed, state).await;
+    }
+    Ok(video)
+}
+
+async fn ban_channel(rid: &i32, by: UserId, state: &AppState) -> anyhow::Result<Option<videos::Model>> {
+    let video = find_video(rid, state).await?;
+    let url = match &video.channel_url {
+        Some(url) => state.metadata.channel_url(url).await,
+        None => None,
+    }.ok_or(anyhow::anyhow!("Unknown channel for {video:?}"))?;
+    Ok(channel::ban(&url, video.channel.clone(), by, state).await?.then_some(video))
 }
 
 // Alternate
@@ -168,10 +362,77 @@ async fn pardon(rid: &i32, state: &AppState) -> anyhow::Result<videos::Model> {
     Ok(video.update(&state.db).await?)
 }
 
-async fn unview(rid: &i32, state: &AppState) -> anyhow::Result<videos::Model> {
+async fn approve(rid: &i32, state: &AppState) -> anyhow::Result<videos::Model> {
     let mut request = requests::Entity::find_by_id(*rid).one(&state.db).await?
         .ok_or(anyhow::anyhow!("Can't find request ID {rid}"))?.into_active_model();
-    request.viewed_at = Set(None);
+    request.flagged = Set(false);
     request.update(&state.db).await?.find_related(videos::Entity).one(&state.db).await?
         .ok_or(anyhow::anyhow!("Can't find video by RID {rid}"))
+}
+
+async fn pardon_channel(rid: &i32, state: &AppState) -> anyhow::Result<Option<videos::Model>> {
+    let video = find_video(rid, state).await?;
+    let url = match &video.channel_url {
+        Some(url) => state.metadata.channel_url(url).await,
+        None => None,
+    }.ok_or(anyhow::anyhow!("Unknown channel for {video:?}"))?;
+    Ok(channel::unban(&url, state).await?.then_some(video))
+}
+
+async fn promote(rid: &i32, state: &AppState) -> anyhow::Result<videos::Model> {
+    let mut request = requests::Entity::find_by_id(*rid).one(&state.db).await?
+        .ok_or(anyhow::anyhow!("Can't find request ID {rid}"))?.into_active_model();
+    request.hidden = Set(false);
+    request.update(&state.db).await?.find_related(videos::Entity).one(&state.db).await?
+        .ok_or(anyhow::anyhow!("Can't find video by RID {rid}"))
+}
+
+/// Удаляет только скрытые запросы, действия удаляются каскадно.
+async fn purge(rid: &i32, state: &AppState) -> anyhow::Result<videos::Model> {
+    let request = requests::Entity::find_by_id(*rid).one(&state.db).await?
+        .filter(|request| request.hidden)
+        .ok_or(anyhow::anyhow!("Can't find hidden request ID {rid}"))?;
+    let video = find_video(rid, state).await?;
+    request.delete(&state.db).await?;
+    Ok(video)
+}
+
+async fn ban_user(rid: &i32, by: UserId, state: &AppState) -> anyhow::Result<UserId> {
+    let uid = find_creator(rid, state).await?;
+    bans::ban(uid, None, None, false, by, state).await?;
+    Ok(uid)
+}
+
+async fn unban_user(rid: &i32, state: &AppState) -> anyhow::Result<Option<UserId>> {
+    let uid = find_creator(rid, state).await?;
+    Ok(bans::unban(uid, state).await?.then_some(uid))
+}
+
+async fn find_creator(rid: &i32, state: &AppState) -> anyhow::Result<UserId> {
+    let creator = actions::Entity::find()
+        .filter(actions::Column::Rid.eq(*rid))
+        .order_by(actions::Column::Id, Order::Asc)
+        .one(&state.db).await?
+        .ok_or(anyhow::anyhow!("Can't find creator for request ID {rid}"))?;
+    Ok(UserId(creator.uid as u64))
+}
+
+async fn find_video(rid: &i32, state: &AppState) -> anyhow::Result<videos::Model> {
+    let request = requests::Entity::find_by_id(*rid).one(&state.db).await?
+        .ok_or(anyhow::anyhow!("Can't find request ID {rid}"))?;
+    request.find_related(videos::Entity).one(&state.db).await?
+        .ok_or(anyhow::anyhow!("Can't find video for {request:?}"))
+}
+
+async fn unview(rid: &i32, state: &AppState) -> anyhow::Result<videos::Model> {
+    let mut request = requests::Entity::find_by_id(*rid).one(&state.db).await?
+        .ok_or(anyhow::anyhow!("Can't find request ID {rid}"))?.into_active_model();
+    request.viewed_at = Set(None);
+    let video = request.update(&state.db).await?.find_related(videos::Entity).one(&state.db).await?
+        .ok_or(anyhow::anyhow!("Can't find video by RID {rid}"))?;
+    // Просмотр отменён: ещё не отправленное "Посмотрели" больше не актуально
+    if let Err(err) = digest::retract(*rid, Outcome::Viewed, state).await {
+        tracing::error!("Caused an exception in digest retract due: {err:?}");
+    }
+    Ok(video)
 }
\ No newline at end of file
@@ -0,0 +1,141 @@
+use std::{collections::HashSet, sync::Arc};
+
+use sea_orm::{prelude::*, sea_query::{Func, LikeExpr, SimpleExpr}, Order, QueryOrder, QuerySelect};
+use teloxide::{prelude::*, types::{
+    InlineKeyboardButton, InlineKeyboardMarkup, InlineQueryResult, InlineQueryResultArticle,
+    InputMessageContent, InputMessageContentText, Me, ParseMode,
+}, utils::html};
+
+use database::{archived, requests, videos};
+use crate::{queue::Queue, AppState};
+use super::vote;
+
+/// Сколько результатов отдавать на запрос (Telegram принимает не больше 50).
+const RESULTS: u64 = 20;
+
+/// Ответ на `@bot <текст>`: видео из очереди и архива с подходящим названием.
+/// Без текста — начало очереди.
+pub async fn answer(bot: Bot, q: InlineQuery, state: Arc<AppState>, me: Me) -> anyhow::Result<()> {
+    let queue = Queue::load(&[], &state).await?;
+    let query = q.query.trim();
+    let mut results = Vec::new();
+    let mut seen = HashSet::new();
+
+    let queued = if query.is_empty() {
+        let mut queued = Vec::new();
+        for (rid, _) in queue.iter().take(RESULTS as usize) {
+            if let Some((request, Some(video))) = requests::Entity::find_by_id(rid)
+                .find_also_related(videos::Entity).one(&state.db).await?
+            {
+                queued.push((request, video));
+            }
+        }
+        queued
+    } else {
+        requests::Entity::find()
+            .find_also_related(videos::Entity)
+            .filter(videos::Column::Banned.eq(false))
+            .filter(requests::Column::Hidden.eq(false))
+            .filter(title_matches(query))
+            .order_by(requests::Column::Id, Order::Desc)
+            .limit(RESULTS)
+            .all(&state.db).await?
+            .into_iter()
+            .filter_map(|(request, video)| Some((request, video?)))
+            .collect()
+    };
+    for (request, video) in queued {
+        let platform = youtube::Platform::from(video.platform.clone());
+        seen.insert((platform.short_name(), video.ytid.clone()));
+        let url = platform.video_url(&video.ytid, request.start_at.map(|s| s as u32));
+        let (status, keyboard) = match (request.viewed_at, queue.position(request.id)) {
+            (Some(viewed_at), _) => (format!("Просмотрено {}", viewed_at.format("%d.%m.%Y")), None),
+            (None, Some(position)) => {
+                let contributors = queue.iter().find(|(rid, _)| *rid == request.id).map(|(_, count)| count).unwrap_or_default();
+                let keyboard = InlineKeyboardMarkup::new(vec![vec![
+                    InlineKeyboardButton::url("👍 Тоже хочу посмотреть", vote::upvote_link(me.username(), request.id).parse()?),
+                ]]);
+                (format!("В очереди №{position}, 🙍‍♂️{contributors}"), Some(keyboard))
+            },
+            (None, None) => continue,
+        };
+        results.push(article(format!("r{}", request.id), &video, &url, platform.short_name(), &status, keyboard));
+    }
+
+    if !query.is_empty() && results.len() < RESULTS as usize {
+        let archived = archived::Entity::find()
+            .find_also_related(videos::Entity)
+            .filter(title_matches(query))
+            .order_by(archived::Column::Id, Order::Desc)
+            .limit(RESULTS * 2)
+            .all(&state.db).await?;
+        for (archived, video) in archived {
+            let Some(video) = video else { continue };
+            let platform = youtube::Platform::from(video.platform.clone());
+            if results.len() >= RESULTS as usize || !seen.insert((platform.short_name(), video.ytid.clone())) {
+                continue;
+            }
+            let url = platform.video_url(&video.ytid, None);
+            let status = match archived.viewed_at {
+                Some(viewed_at) => format!("В архиве, просмотрено {}", viewed_at.format("%d.%m.%Y")),
+                None => "В архиве".to_string(),
+            };
+            results.push(article(format!("a{}", archived.id), &video, &url, platform.short_name(), &status, None));
+        }
+    }
+
+    bot.answer_inline_query(&q.id, results).cache_time(10).await?;
+    Ok(())
+}
+
+/// Карточка видео, которую пользователь отправит в чат.
+fn article(id: String, video: &videos::Model, url: &str, platform: &str, status: &str, keyboard: Option<InlineKeyboardMarkup>) -> InlineQueryResult {
+    let mut text = format!("<b>{}</b>\n<a href=\"{url}\">📺{platform}</a>", html::escape(&video.title));
+    if let Some(channel) = &video.channel {
+        text.push_str(&format!(" {}", html::escape(channel)));
+    }
+    if let Some(duration) = video.duration {
+        text.push_str(&format!(" ⏱{}", youtube::format_duration(duration as u32)));
+    }
+    text.push_str(&format!("\n{status}"));
+    let content = InputMessageContent::Text(InputMessageContentText::new(text).parse_mode(ParseMode::Html));
+    let mut article = InlineQueryResultArticle::new(id, &video.title, content).description(status);
+    if let Some(thumbnail) = video.thumbnail.as_deref().and_then(|thumbnail| thumbnail.parse().ok()) {
+        article = article.thumbnail_url(thumbnail);
+    }
+    if let Some(keyboard) = keyboard {
+        article = article.reply_markup(keyboard);
+    }
+    InlineQueryResult::Article(article)
+}
+
+/// Регистронезависимый поиск подстроки в названии видео.
+fn title_matches(query: &str) -> SimpleExpr {
+    Expr::expr(Func::lower(Expr::col((videos::Entity, videos::Column::Title))))
+        .like(LikeExpr::new(like_pattern(query)).escape('\\'))
+}
+
+/// Шаблон LIKE для подстроки: спецсимволы из запроса экранируются.
+fn like_pattern(query: &str) -> String {
+    let mut pattern = String::from("%");
+    for c in query.to_lowercase().chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    pattern
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_like_pattern() {
+        assert_eq!(like_pattern("Собака"), "%собака%");
+        assert_eq!(like_pattern("100% fun_run"), "%100\\% fun\\_run%");
+        assert_eq!(like_pattern("a\\b"), "%a\\\\b%");
+    }
+}
@@ -1,24 +1,46 @@
 use std::sync::Arc;
 
 use indexmap::IndexMap;
-use teloxide::{prelude::*, types::{InlineKeyboardButton, InlineKeyboardMarkup, LinkPreviewOptions, ParseMode}};
+use teloxide::{prelude::*, types::{InlineKeyboardButton, InlineKeyboardMarkup, LinkPreviewOptions, ParseMode}, utils::html};
 use sea_orm::{prelude::*, Order, QueryOrder};
 
-use database::*;
-
-use crate::AppState;
-
-struct Video {
-    id: i32,
-    title: String,
-    url: String,
-    contributors: u64,
-    status: String,
+use database::{*, sea_orm_active_enums::Status};
+
+use crate::{markup, queue, AppState};
+
+/// Сколько символов заметки показывать в списке.
+const NOTE_PREVIEW_LEN: usize = 40;
+
+pub(super) struct Video {
+    pub id: i32,
+    pub title: String,
+    pub url: String,
+    pub platform: youtube::Platform,
+    pub contributors: u64,
+    pub status: String,
+    pub channel: Option<String>,
+    pub duration: Option<i32>,
+    pub clip: Option<String>,
+    pub note: Option<String>,
+    /// UID предложившего первым
+    pub creator: i64,
 }
 
-pub async fn command(bot: Bot, msg: Message, state: Arc<AppState>) -> anyhow::Result<()> {
-    let videos: Vec<(requests::Model, Option<videos::Model>)> = requests::Entity::find()
-        .find_also_related(videos::Entity).filter(videos::Column::Banned.eq(false)).all(&state.db).await?;
+pub async fn command(bot: Bot, msg: Message, state: Arc<AppState>, filter: String) -> anyhow::Result<()> {
+    let mut query = requests::Entity::find()
+        .find_also_related(videos::Entity).filter(videos::Column::Banned.eq(false)).filter(requests::Column::Hidden.eq(false));
+    // Фильтр "влезает в оставшееся время": /list <минуты>
+    let filter = filter.trim();
+    if !filter.is_empty() {
+        let Ok(minutes) = filter.parse::<i32>() else {
+            bot.send_message(msg.chat.id, "После команды можно указать количество минут. (/list 30)").await?;
+            return Ok(());
+        };
+        query = query
+            .filter(requests::Column::ViewedAt.is_null())
+            .filter(videos::Column::Duration.lte(minutes.saturating_mul(60)));
+    }
+    let videos: Vec<(requests::Model, Option<videos::Model>)> = query.all(&state.db).await?;
 
     let result = generate_list(videos, &state).await;
     match result {
@@ -53,7 +75,7 @@ pub async fn command(bot: Bot, msg: Message, state: Arc<AppState>) -> anyhow::Re
 pub async fn inline(state: Arc<AppState>, bot: Bot, q: CallbackQuery) -> anyhow::Result<()> {
     bot.answer_callback_query(&q.id).await?;
     let videos: Vec<(requests::Model, Option<videos::Model>)> = requests::Entity::find()
-        .find_also_related(videos::Entity).filter(videos::Column::Banned.eq(false)).filter(requests::Column::ViewedAt.is_null()).all(&state.db).await?;
+        .find_also_related(videos::Entity).filter(videos::Column::Banned.eq(false)).filter(requests::Column::Hidden.eq(false)).filter(requests::Column::ViewedAt.is_null()).all(&state.db).await?;
     let result = generate_list(videos, &state).await;
     match result {
         Ok(list) => {
@@ -99,29 +121,36 @@ pub async fn inline(state: Arc<AppState>, bot: Bot, q: CallbackQuery) -> anyhow:
     Ok(())
 }
 
-async fn generate_list(videos: Vec<(requests::Model, Option<videos::Model>)>, state: &AppState) -> anyhow::Result<Option<String>> {
-    if videos.is_empty() {
-        return Ok(None);
-    }
+/// Собирает данные для списка: по дням, сначала новые, внутри дня по числу участников.
+pub(super) async fn collect(videos: Vec<(requests::Model, Option<videos::Model>)>, state: &AppState) -> anyhow::Result<IndexMap<Date, Vec<Video>>> {
     let mut by_date: IndexMap<Date, Vec<Video>> = IndexMap::new();
     for (request, video) in videos {
         let video = video.unwrap();
-        let creator = if let Some(c) = request.find_related(actions::Entity).order_by(actions::Column::Id, Order::Asc).one(&state.db).await? {
+        let actions = request.find_related(actions::Entity).order_by(actions::Column::Id, Order::Asc).all(&state.db).await?;
+        let creator = if let Some(c) = actions.first() {
             c
         } else {
             anyhow::bail!("Can't find creator for {request:?}");
         };
 
-        let contributors = request.find_related(actions::Entity).count(&state.db).await?;
+        let contributors = actions.iter().filter(|action| queue::counts(&request, action)).count() as u64;
+        // В списке показываем только первую заметку
+        let note = actions.iter().find_map(|action| action.note.as_deref()).map(|note| markup::truncate(note, NOTE_PREVIEW_LEN));
         let date = creator.created_at.date();
-        let url = format!("{}{}", youtube::DEFAULT_YT, video.ytid);
+        let platform = youtube::Platform::from(video.platform.clone());
+        let url = platform.video_url(&video.ytid, request.start_at.map(|s| s as u32));
 
-        let viewed_times = archived::Entity::find().filter(archived::Column::Ytid.eq(video.ytid.clone())).filter(archived::Column::ViewedAt.is_not_null()).count(&state.db).await?;
-        let archived_times = archived::Entity::find().filter(archived::Column::Ytid.eq(video.ytid)).count(&state.db).await?;
+        let viewed_times = archived::Entity::find().filter(archived::Column::Platform.eq(video.platform.clone())).filter(archived::Column::Ytid.eq(video.ytid.clone())).filter(archived::Column::ViewedAt.is_not_null()).count(&state.db).await?;
+        let archived_times = archived::Entity::find().filter(archived::Column::Platform.eq(video.platform.clone())).filter(archived::Column::Ytid.eq(video.ytid)).count(&state.db).await?;
 
         let mut status = String::new();
+        if request.flagged {
+            status.push('⚠');
+        }
         status.push(if request.viewed_at.is_some() {
             '👀'
+        } else if request.status == Status::Unavailable {
+            '🚫'
         } else if viewed_times != 0 {
             '⭐'
         } else if archived_times != 0 {
@@ -130,29 +159,49 @@ async fn generate_list(videos: Vec<(requests::Model, Option<videos::Model>)>, st
             '🆕'
         });
 
-        if let Some(entry) = by_date.get_mut(&date) {
-            entry.push(Video { id: request.id, title: video.title, url, contributors, status });
-        } else {
-            by_date.insert(date, vec![Video { id: request.id, title: video.title, url, contributors, status }]);
-        };
+        let entry = Video { id: request.id, title: video.title, url, platform, contributors, status, channel: video.channel, duration: video.duration, clip: markup::clip_label(request.start_at, request.end_at), note, creator: creator.uid };
+        by_date.entry(date).or_default().push(entry);
     }
     by_date.sort_unstable_by(|a, _, c, _| c.cmp(a));
+    for videos in by_date.values_mut() {
+        // Внутри дня популярные выше, как и в очереди для /vote и /status
+        videos.sort_unstable_by_key(|video| (std::cmp::Reverse(video.contributors), video.id));
+    }
+    Ok(by_date)
+}
+
+async fn generate_list(videos: Vec<(requests::Model, Option<videos::Model>)>, state: &AppState) -> anyhow::Result<Option<String>> {
+    if videos.is_empty() {
+        return Ok(None);
+    }
+    let by_date = collect(videos, state).await?;
     let mut result = String::new();
-    for (date, mut videos) in by_date {
+    for (date, videos) in by_date {
         if result.is_empty() {
             result.push_str(&format!("[{}]", date.format("%d.%m")));
         } else {
             result.push_str(&format!("\n[{}]", date.format("%d.%m")));
         }
         // result.push_str(&format!(" {}", videos.len()));
-        videos.sort_unstable_by(|a, b| a.contributors.cmp(&b.contributors));
         for video in videos {
             let contributors = if video.contributors != 1 {
                 format!("(🙍‍♂️{}) ", video.contributors)
             } else {
                 String::new()
             };
-            result.push_str(&format!("\n{}/{} <a href=\"{}\">📺YT</a> {}<b>{}</b>", video.status, video.id, video.url, contributors, video.title));
+            result.push_str(&format!("\n{}/{} <a href=\"{}\">📺{}</a> {}<b>{}</b>", video.status, video.id, video.url, video.platform.short_name(), contributors, video.title));
+            if let Some(channel) = video.channel {
+                result.push_str(&format!(" — <i>{}</i>", html::escape(&channel)));
+            }
+            if let Some(duration) = video.duration {
+                result.push_str(&format!(" ⏱{}", youtube::format_duration(duration as u32)));
+            }
+            if let Some(clip) = video.clip {
+                result.push_str(&format!(" ▶{clip}"));
+            }
+            if let Some(note) = video.note {
+                result.push_str(&format!(" 💬<i>{}</i>", html::escape(&note)));
+            }
             // result.push_str(&format!("\n<a href=\"tg://resolve?domain={}&start=info%20{}\">{}.</a> <b>{}</b> <a href=\"{DEFAULT_YT}{}\">YT</a> ({})", me.username.clone().unwrap(), video.id, video.id, video.title, video.url, video.contributors));
         }
     }
@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use dptree::{filter, filter_map};
+use dptree::{filter, filter_async, filter_map};
 use teloxide::{dispatching::{dialogue::{self, GetChatId, InMemStorage}, HandlerExt, UpdateHandler}, prelude::*, types::User};
 
 use crate::{cancel, AppState, Command, DialogueState, InlineCommand, Rights};
@@ -14,27 +14,60 @@ mod info;
 mod start;
 mod archive;
 mod notify;
+mod channel;
+mod filter;
+mod bans;
+mod shadow;
+mod my;
+mod status;
+mod vote;
+mod queue;
+mod inline_query;
 
 pub fn schema() -> UpdateHandler<anyhow::Error> {
     use dptree::case;
     let moderator_commands = dptree::entry()
-        .branch(case![Command::Start].endpoint(start::command_mod))
+        .branch(case![Command::Start(payload)].endpoint(start::command_mod))
         .branch(case![Command::Help].endpoint(start::command_mod))
-        .branch(case![Command::List].endpoint(list::command))
+        .branch(case![Command::List(filter)].endpoint(list::command))
         .branch(case![Command::Archive].endpoint(archive::command))
         .branch(case![Command::Mods].endpoint(moderator::list::command))
         .branch(case![Command::AddMod].endpoint(moderator::add::command))
         .branch(case![Command::RemMod(uid)].endpoint(moderator::remove::command))
+        .branch(case![Command::BanChannel(url)].endpoint(channel::ban_command))
+        .branch(case![Command::UnbanChannel(url)].endpoint(channel::unban_command))
+        .branch(case![Command::Channels].endpoint(channel::list_command))
+        .branch(case![Command::Ban(args)].endpoint(bans::ban_command))
+        .branch(case![Command::TempBan(args)].endpoint(bans::tempban_command))
+        .branch(case![Command::Unban(uid)].endpoint(bans::unban_command))
+        .branch(case![Command::ShadowBan(args)].endpoint(bans::shadowban_command))
+        .branch(case![Command::Bans].endpoint(bans::list_command))
+        .branch(case![Command::Shadow].endpoint(shadow::command))
+        .branch(case![Command::Filter(args)].endpoint(filter::command))
         .branch(case![Command::Notify].endpoint(notify::command))
+        .branch(case![Command::My].endpoint(my::command))
+        .branch(case![Command::Status(link)].endpoint(status::command))
+        .branch(case![Command::Vote].endpoint(vote::command))
+        .branch(case![Command::Queue].endpoint(queue::command))
         .branch(case![Command::About].endpoint(about::command));
 
     let user_commands = dptree::entry()
-        .branch(case![Command::Start].endpoint(start::command_user))
+        .branch(case![Command::Start(payload)].endpoint(start::command_user))
+        .branch(case![Command::My].endpoint(my::command))
+        .branch(case![Command::Status(link)].endpoint(status::command))
+        .branch(case![Command::Vote].endpoint(vote::command))
+        .branch(case![Command::Queue].endpoint(queue::command))
+        .branch(case![Command::Notify].endpoint(notify::command_user))
         .branch(case![Command::About].endpoint(about::command));
 
     let command_handler = dptree::entry()
         .filter_command::<Command>()
         .branch(case![DialogueState::Nothing]
+            // /start upvote_<rid> из карточки inline-режима
+            .branch(filter_map(|command: Command| match command {
+                Command::Start(payload) => vote::parse_start(&payload),
+                _ => None,
+            }).endpoint(vote::start))
             .branch(case![Rights::None].branch(user_commands))
             .branch(case![Rights::Moderator { can_add_mods }].branch(moderator_commands.clone()))
         );
@@ -49,8 +82,10 @@ pub fn schema() -> UpdateHandler<anyhow::Error> {
         .filter_map_async(|state: Arc<AppState>, uid: UserId| async move {
             state.check_rights(&uid).await.ok()
         })
+        .branch(case![Rights::Banned { reason, until }].endpoint(bans::message))
         // State handlers
         .branch(case![DialogueState::NewModeratorInput].endpoint(moderator::add::recieved_message))
+        .branch(case![DialogueState::VideoNote { uid, video }].endpoint(add::note_message))
         .branch(command_handler)
         .branch(
             dptree::filter_map(|msg: Message| {
@@ -64,7 +99,7 @@ pub fn schema() -> UpdateHandler<anyhow::Error> {
         )
         .branch(
             dptree::filter(|msg: Message| {
-                msg.text().is_some() && msg.from.is_some()
+                (msg.text().is_some() || msg.caption().is_some()) && msg.from.is_some()
             })
             .endpoint(add::message)
         );
@@ -74,13 +109,27 @@ pub fn schema() -> UpdateHandler<anyhow::Error> {
             InlineCommand::parse(&q.data?)
         }))
         .branch(case![InlineCommand::Cancel].endpoint(cancel))
-        .branch(case![InlineCommand::ListUnviewed].endpoint(list::inline))
+        .branch(case![InlineCommand::My(page)].endpoint(my::inline))
+        .branch(case![InlineCommand::Withdraw(rid, page)].endpoint(my::withdraw_inline))
+        .branch(case![InlineCommand::Vote(page)].endpoint(vote::inline))
+        .branch(case![InlineCommand::Queue(page)].endpoint(queue::inline))
+        .branch(case![InlineCommand::Upvote(rid, page)].endpoint(vote::upvote_inline))
+        // Кнопки модераторов: данные кнопки можно подделать, поэтому права проверяются при нажатии
         .branch(filter(|com: InlineCommand| {
-            matches!(com, InlineCommand::ArchiveAll | InlineCommand::ArchiveViewed)
-        }).endpoint(archive::inline))
-        .branch(filter(|com: InlineCommand| {
-            matches!(com, InlineCommand::Ban(_) | InlineCommand::Pardon(_) | InlineCommand::View(_) | InlineCommand::Unview(_))
-        }).endpoint(info::inline));
+            matches!(com, InlineCommand::ArchiveAll | InlineCommand::ArchiveViewed | InlineCommand::ListUnviewed
+                | InlineCommand::Ban(_) | InlineCommand::Pardon(_) | InlineCommand::View(_) | InlineCommand::Unview(_)
+                | InlineCommand::BanChannel(_) | InlineCommand::PardonChannel(_) | InlineCommand::Approve(_)
+                | InlineCommand::BanUser(_) | InlineCommand::UnbanUser(_) | InlineCommand::Promote(_) | InlineCommand::Purge(_))
+        })
+            .branch(filter_async(is_moderator)
+                .branch(case![InlineCommand::ListUnviewed].endpoint(list::inline))
+                .branch(filter(|com: InlineCommand| {
+                    matches!(com, InlineCommand::ArchiveAll | InlineCommand::ArchiveViewed)
+                }).endpoint(archive::inline))
+                .branch(dptree::endpoint(info::inline))
+            )
+            .branch(dptree::endpoint(not_moderator))
+        );
 
     let callback_query_handler = Update::filter_callback_query()
         .filter_map(|q: CallbackQuery| {
@@ -95,9 +144,27 @@ pub fn schema() -> UpdateHandler<anyhow::Error> {
         .branch(parsable_callback)
         // FIXME: .branch(case![DialogueState::Nothing].endpoint(info::inline))
         .branch(case![DialogueState::RemoveModeratorConfirm { uid }].endpoint(moderator::remove::inline))
-        .branch(case![DialogueState::AcceptVideo { ytid, uid, title }].endpoint(add::inline));
+        .branch(case![DialogueState::AcceptVideo { uid, video }].endpoint(add::inline))
+        .branch(case![DialogueState::VideoNote { uid, video }].endpoint(add::inline_note))
+        .branch(case![DialogueState::AcceptVideos { uid, videos }].endpoint(add::inline_batch))
+        .branch(case![DialogueState::PickVideo { uid, results }].endpoint(add::inline_pick));
+
+    // У inline-запросов нет чата, поэтому они обрабатываются вне диалога
+    let inline_query_handler = Update::filter_inline_query().endpoint(inline_query::answer);
+
+    dptree::entry()
+        .branch(inline_query_handler)
+        .branch(dialogue::enter::<Update, InMemStorage<DialogueState>, DialogueState, _>()
+            .branch(message_handler)
+            .branch(callback_query_handler)
+        )
+}
+
+async fn is_moderator(q: CallbackQuery, state: Arc<AppState>) -> bool {
+    matches!(state.check_rights(&q.from.id).await, Ok(Rights::Moderator { .. }))
+}
 
-    dialogue::enter::<Update, InMemStorage<DialogueState>, DialogueState, _>()
-        .branch(message_handler)
-        .branch(callback_query_handler)
-}
\ No newline at end of file
+async fn not_moderator(bot: Bot, q: CallbackQuery) -> anyhow::Result<()> {
+    bot.answer_callback_query(&q.id).text("Недостаточно прав!").show_alert(true).await?;
+    Ok(())
+}
@@ -0,0 +1,178 @@
+use std::sync::Arc;
+
+use sea_orm::{prelude::*, IntoActiveModel, Order, QueryOrder, QuerySelect, Set, TransactionTrait};
+use teloxide::{prelude::*, types::{InlineKeyboardButton, InlineKeyboardMarkup, ParseMode}, utils::html};
+
+use database::{actions, archived, requests, sea_orm_active_enums::Status, users, videos};
+use crate::{markup, queue::{self, Queue}, AppState};
+
+/// Записей на одной странице.
+const PAGE_SIZE: usize = 10;
+
+struct Entry {
+    text: String,
+    /// Запрос, от которого пользователь ещё может отказаться, и его название
+    withdrawable: Option<(i32, String)>,
+}
+
+pub async fn command(bot: Bot, msg: Message, uid: UserId, state: Arc<AppState>) -> anyhow::Result<()> {
+    let (text, keyboard) = render(uid, 0, &state).await?;
+    bot.send_message(msg.chat.id, text).parse_mode(ParseMode::Html).reply_markup(keyboard).await?;
+    Ok(())
+}
+
+/// Переключение страниц.
+pub async fn inline(bot: Bot, q: CallbackQuery, state: Arc<AppState>, page: u32) -> anyhow::Result<()> {
+    bot.answer_callback_query(&q.id).await?;
+    let (text, keyboard) = render(q.from.id, page, &state).await?;
+    if let Some(message) = q.regular_message() {
+        bot.edit_message_text(message.chat.id, message.id, text).parse_mode(ParseMode::Html).reply_markup(keyboard).await?;
+    }
+    Ok(())
+}
+
+async fn render(uid: UserId, page: u32, state: &AppState) -> anyhow::Result<(String, InlineKeyboardMarkup)> {
+    let entries = collect_entries(uid, state).await?;
+    if entries.is_empty() {
+        return Ok(("Вы ещё ничего не предлагали.".to_string(), InlineKeyboardMarkup::default()));
+    }
+    let (range, pages) = markup::page_bounds(entries.len(), page as usize, PAGE_SIZE);
+    let page = range.start / PAGE_SIZE;
+    let mut text = format!("Ваши предложения ({}/{pages}):", page + 1);
+    let mut keyboard = Vec::new();
+    for entry in &entries[range] {
+        text.push('\n');
+        text.push_str(&entry.text);
+        if let Some((rid, title)) = &entry.withdrawable {
+            let label = format!("Отозвать: {}", markup::truncate(title, 40));
+            keyboard.push(vec![withdraw_button(label, *rid, Some(page as u32))]);
+        }
+    }
+    keyboard.push(markup::pager("my", page, pages));
+    Ok((text, InlineKeyboardMarkup::new(keyboard)))
+}
+
+/// Кнопка отзыва своего предложения. `page` — страница /my, которую нужно перерисовать.
+pub fn withdraw_button(label: impl Into<String>, rid: i32, page: Option<u32>) -> InlineKeyboardButton {
+    let data = match page {
+        Some(page) => format!("withdraw {rid} {page}"),
+        None => format!("withdraw {rid}"),
+    };
+    InlineKeyboardButton::callback(label, data)
+}
+
+/// Отзыв своего предложения.
+pub async fn withdraw_inline(bot: Bot, q: CallbackQuery, state: Arc<AppState>, (rid, page): (i32, Option<u32>)) -> anyhow::Result<()> {
+    let text = match withdraw(rid, q.from.id, &state).await {
+        Ok(Withdrawal::Done) => "Предложение отозвано.",
+        Ok(Withdrawal::NotFound) => "Это предложение уже отозвано.",
+        Ok(Withdrawal::Viewed) => "Видео уже просмотрено, отозвать нельзя.",
+        Err(err) => {
+            tracing::error!("Caused an exception in withdraw due: {err:?}");
+            "Произошла ошибка!"
+        },
+    };
+    bot.answer_callback_query(&q.id).text(text).await?;
+    let Some(message) = q.regular_message() else {
+        return Ok(());
+    };
+    match page {
+        Some(page) => {
+            let (list, keyboard) = render(q.from.id, page, &state).await?;
+            bot.edit_message_text(message.chat.id, message.id, list).parse_mode(ParseMode::Html).reply_markup(keyboard).await?;
+        },
+        None => {
+            bot.edit_message_text(message.chat.id, message.id, text).await?;
+        },
+    }
+    Ok(())
+}
+
+enum Withdrawal {
+    Done,
+    NotFound,
+    Viewed,
+}
+
+/// Удаляет вклад пользователя, а запрос целиком — если других вкладов нет.
+async fn withdraw(rid: i32, uid: UserId, state: &AppState) -> anyhow::Result<Withdrawal> {
+    let txn = state.db.begin().await?;
+    let Some(request) = requests::Entity::find_by_id(rid).one(&txn).await? else {
+        return Ok(Withdrawal::NotFound);
+    };
+    if request.viewed_at.is_some() {
+        return Ok(Withdrawal::Viewed);
+    }
+    let Some(action) = request.find_related(actions::Entity)
+        .filter(actions::Column::Uid.eq(uid.0 as i64))
+        .one(&txn).await? else {
+        return Ok(Withdrawal::NotFound);
+    };
+    let counted = action.counted;
+    action.delete(&txn).await?;
+    if request.find_related(actions::Entity).count(&txn).await? == 0 {
+        request.delete(&txn).await?;
+    }
+    // Предложения из теневого бана в статистику не попадали
+    if counted {
+        if let Some(user) = users::Entity::find_by_id(uid.0 as i64).one(&txn).await? {
+            let contributions = user.contributions;
+            let mut user = user.into_active_model();
+            user.contributions = Set((contributions - 1).max(0));
+            user.update(&txn).await?;
+        }
+    }
+    txn.commit().await?;
+    Ok(Withdrawal::Done)
+}
+
+/// Сначала открытые запросы, затем архив, новые выше.
+async fn collect_entries(uid: UserId, state: &AppState) -> anyhow::Result<Vec<Entry>> {
+    let mut entries = Vec::new();
+
+    let open: Vec<(requests::Model, Option<videos::Model>)> = requests::Entity::find()
+        .inner_join(actions::Entity)
+        .filter(actions::Column::Uid.eq(uid.0 as i64))
+        .find_also_related(videos::Entity)
+        .order_by(requests::Column::Id, Order::Desc)
+        .all(&state.db).await?;
+    let own: Vec<i32> = open.iter().map(|(request, _)| request.id).collect();
+    let queue = Queue::load(&own, state).await?;
+    for (request, video) in open {
+        let Some(video) = video else { continue };
+        let title = html::escape(&video.title);
+        let mut withdrawable = None;
+        let text = if let Some(viewed_at) = request.viewed_at {
+            format!("👀 <b>{title}</b> — просмотрено {}", viewed_at.format("%d.%m"))
+        } else if video.banned {
+            format!("⛔ <b>{title}</b> — отклонено")
+        } else if request.status == Status::Unavailable {
+            format!("🚫 <b>{title}</b> — стало недоступно")
+        } else {
+            // Свой вклад пользователь видит всегда, даже из теневого бана
+            let contributors = request.find_related(actions::Entity).all(&state.db).await?
+                .iter().filter(|action| queue::counts(&request, action) || action.uid == uid.0 as i64)
+                .count();
+            let position = queue.position(request.id).unwrap_or_default();
+            withdrawable = Some((request.id, video.title.clone()));
+            format!("🕒 <b>{title}</b> — №{position} в очереди, 🙍‍♂️{contributors}")
+        };
+        entries.push(Entry { text, withdrawable });
+    }
+
+    let history: Vec<(archived::Model, Option<videos::Model>)> = archived::Entity::find()
+        .filter(archived::Column::CreatedBy.eq(uid.0 as i64))
+        .find_also_related(videos::Entity)
+        .order_by(archived::Column::Id, Order::Desc)
+        .limit(100)
+        .all(&state.db).await?;
+    for (entry, video) in history {
+        let title = video.map(|video| html::escape(&video.title)).unwrap_or(entry.ytid);
+        let text = match entry.viewed_at {
+            Some(viewed_at) => format!("📁👀 <b>{title}</b> — просмотрено {}", viewed_at.format("%d.%m")),
+            None => format!("📁⏭ <b>{title}</b> — пропущено"),
+        };
+        entries.push(Entry { text, withdrawable: None });
+    }
+    Ok(entries)
+}
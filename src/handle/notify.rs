@@ -34,6 +34,32 @@ pub async fn command(bot: Bot, msg: Message, uid: UserId, state: Arc<AppState>)
         text
     };
 
+    bot.send_message(msg.chat.id, text).parse_mode(teloxide::types::ParseMode::Html).await?;
+    Ok(())
+}
+
+/// Invert digest subscription for user
+pub async fn command_user(bot: Bot, msg: Message, uid: UserId, state: Arc<AppState>) -> anyhow::Result<()> {
+    let user = if let Some(user) = users::Entity::find_by_id(uid.0 as i64).one(&state.db).await? {
+        let notify = user.notify;
+        let mut user = user.into_active_model();
+        user.notify = Set(!notify);
+        user.update(&state.db).await?
+    } else {
+        // Ещё ничего не предлагал, но подписаться можно заранее
+        users::ActiveModel {
+            id: Set(uid.0 as i64),
+            contributions: Set(0),
+            notify: Set(true),
+            ..Default::default()
+        }.insert(&state.db).await?
+    };
+
+    let text = if user.notify {
+        "Теперь уведомления о ваших видео <b>включены</b>!\nНовости приходят одним сообщением раз в несколько минут."
+    } else {
+        "Теперь уведомления о ваших видео <b>отключены</b>!"
+    };
     bot.send_message(msg.chat.id, text).parse_mode(teloxide::types::ParseMode::Html).await?;
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,93 @@
+use std::sync::Arc;
+
+use teloxide::{prelude::*, types::{InlineKeyboardButton, InlineKeyboardMarkup, LinkPreviewOptions, ParseMode}, utils::html::{self, user_mention}};
+use sea_orm::prelude::*;
+
+use database::{requests, videos};
+use crate::{markup, AppState, QUEUE_SHOW_AUTHORS};
+use super::list;
+
+/// Записей на одной странице.
+const PAGE_SIZE: usize = 15;
+
+/// Очередь непросмотренных для всех, без управления и (по настройке) без авторов.
+pub async fn command(bot: Bot, msg: Message, state: Arc<AppState>) -> anyhow::Result<()> {
+    let (text, keyboard) = render(&bot, 0, &state).await?;
+    bot.send_message(msg.chat.id, text).parse_mode(ParseMode::Html)
+        .link_preview_options(LinkPreviewOptions {
+            is_disabled: true,
+            url: None,
+            prefer_small_media: false,
+            prefer_large_media: false,
+            show_above_text: false
+        }).reply_markup(keyboard).await?;
+    Ok(())
+}
+
+/// Переключение страниц.
+pub async fn inline(bot: Bot, q: CallbackQuery, state: Arc<AppState>, page: u32) -> anyhow::Result<()> {
+    bot.answer_callback_query(&q.id).await?;
+    let (text, keyboard) = render(&bot, page, &state).await?;
+    if let Some(message) = q.regular_message() {
+        bot.edit_message_text(message.chat.id, message.id, text).parse_mode(ParseMode::Html)
+            .link_preview_options(LinkPreviewOptions {
+                is_disabled: true,
+                url: None,
+                prefer_small_media: false,
+                prefer_large_media: false,
+                show_above_text: false
+            }).reply_markup(keyboard).await?;
+    }
+    Ok(())
+}
+
+async fn render(bot: &Bot, page: u32, state: &AppState) -> anyhow::Result<(String, InlineKeyboardMarkup)> {
+    let videos: Vec<(requests::Model, Option<videos::Model>)> = requests::Entity::find()
+        .find_also_related(videos::Entity)
+        .filter(videos::Column::Banned.eq(false))
+        .filter(requests::Column::Hidden.eq(false))
+        .filter(requests::Column::ViewedAt.is_null())
+        .all(&state.db).await?;
+    if videos.is_empty() {
+        return Ok(("Очередь пуста, самое время что-нибудь предложить!".to_string(), InlineKeyboardMarkup::default()));
+    }
+    let entries: Vec<(Date, list::Video)> = list::collect(videos, state).await?.into_iter()
+        .flat_map(|(date, videos)| videos.into_iter().map(move |video| (date, video)))
+        .collect();
+    let (range, pages) = markup::page_bounds(entries.len(), page as usize, PAGE_SIZE);
+    let page = range.start / PAGE_SIZE;
+
+    let mut text = format!("Очередь на просмотр ({}/{pages}):", page + 1);
+    let mut last_date = None;
+    for (index, (date, video)) in entries.into_iter().enumerate().skip(range.start).take(range.len()) {
+        if last_date != Some(date) {
+            text.push_str(&format!("\n[{}]", date.format("%d.%m")));
+            last_date = Some(date);
+        }
+        text.push_str(&format!("\n{}. <a href=\"{}\">📺{}</a> <b>{}</b>", index + 1, video.url, video.platform.short_name(), html::escape(&video.title)));
+        if let Some(channel) = video.channel {
+            text.push_str(&format!(" — <i>{}</i>", html::escape(&channel)));
+        }
+        if let Some(duration) = video.duration {
+            text.push_str(&format!(" ⏱{}", youtube::format_duration(duration as u32)));
+        }
+        if let Some(clip) = video.clip {
+            text.push_str(&format!(" ▶{clip}"));
+        }
+        if video.contributors != 1 {
+            text.push_str(&format!(" (🙍‍♂️{})", video.contributors));
+        }
+        if *QUEUE_SHOW_AUTHORS {
+            let uid = UserId(video.creator as u64);
+            // Пользователь мог удалить аккаунт или закрыть профиль
+            if let Ok(member) = bot.get_chat_member(ChatId(video.creator), uid).await {
+                text.push_str(&format!(" от {}", user_mention(uid, &member.user.full_name())));
+            }
+        }
+    }
+    let keyboard = vec![
+        markup::pager("queue", page, pages),
+        vec![InlineKeyboardButton::callback("Голосовать", "vote 0")],
+    ];
+    Ok((text, InlineKeyboardMarkup::new(keyboard)))
+}
@@ -0,0 +1,55 @@
+use std::sync::Arc;
+
+use sea_orm::{prelude::*, Order, QueryOrder};
+use teloxide::{prelude::*, types::{InlineKeyboardButton, InlineKeyboardMarkup, LinkPreviewOptions, ParseMode}, utils::html::{self, user_mention}};
+
+use database::{actions, requests, videos};
+use crate::AppState;
+
+/// Кнопки в Telegram ограничены сотней, по две на запрос.
+const MAX_BUTTON_ROWS: usize = 50;
+
+/// Видео пользователей в теневом бане, их можно показать в очереди или удалить.
+pub async fn command(bot: Bot, msg: Message, state: Arc<AppState>) -> anyhow::Result<()> {
+    let hidden: Vec<(requests::Model, Option<videos::Model>)> = requests::Entity::find()
+        .find_also_related(videos::Entity)
+        .filter(requests::Column::Hidden.eq(true))
+        .order_by(requests::Column::Id, Order::Asc)
+        .all(&state.db).await?;
+    if hidden.is_empty() {
+        bot.send_message(msg.chat.id, "Скрытых видео нет.").await?;
+        return Ok(());
+    }
+
+    let mut text = String::from("👻 Скрытые видео:");
+    let mut keyboard: Vec<Vec<InlineKeyboardButton>> = Vec::new();
+    for (request, video) in hidden {
+        let Some(video) = video else { continue };
+        let creator = request.find_related(actions::Entity)
+            .order_by(actions::Column::Id, Order::Asc)
+            .one(&state.db).await?
+            .ok_or(anyhow::anyhow!("Can't find creator for {request:?}"))?;
+        let platform = youtube::Platform::from(video.platform.clone());
+        let url = platform.video_url(&video.ytid, request.start_at.map(|s| s as u32));
+        text.push_str(&format!(
+            "\n/{} <a href=\"{url}\">📺{}</a> <b>{}</b> — {}",
+            request.id, platform.short_name(), html::escape(&video.title),
+            user_mention(UserId(creator.uid as u64), &creator.uid.to_string())
+        ));
+        if keyboard.len() < MAX_BUTTON_ROWS {
+            keyboard.push(vec![
+                InlineKeyboardButton::callback(format!("Показать /{}", request.id), format!("promote {}", request.id)),
+                InlineKeyboardButton::callback(format!("Удалить /{}", request.id), format!("purge {}", request.id)),
+            ]);
+        }
+    }
+    bot.send_message(msg.chat.id, text).parse_mode(ParseMode::Html)
+        .link_preview_options(LinkPreviewOptions {
+            is_disabled: true,
+            url: None,
+            prefer_small_media: false,
+            prefer_large_media: false,
+            show_above_text: false
+        }).reply_markup(InlineKeyboardMarkup::new(keyboard)).await?;
+    Ok(())
+}
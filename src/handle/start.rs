@@ -1,8 +1,8 @@
-use teloxide::{prelude::*, types::{InputFile, User}, utils::command::BotCommands as _};
+use teloxide::{prelude::*, types::{InputFile, Me, User}, utils::command::BotCommands as _};
 
 use crate::Command;
 
-pub async fn command_user(bot: Bot, msg: Message, user: User) -> anyhow::Result<()> {
+pub async fn command_user(bot: Bot, msg: Message, user: User, me: Me) -> anyhow::Result<()> {
     bot.send_sticker(
             msg.chat.id, 
             InputFile::file_id("CAACAgIAAxkBAAECxFlnVeGjr8kRcDNWU30uDII5R1DwNAACKl4AAkxE8UmPev9DDR6RgTYE")
@@ -10,8 +10,15 @@ pub async fn command_user(bot: Bot, msg: Message, user: User) -> anyhow::Result<
         .await?;
     bot.send_message(msg.chat.id, format!(
             "Приветствую {}!\n\
-            Отправьте в этот чат ссылку на YouTube видео, чтобы предложить его для просмотра!",
-            user.full_name()
+            Отправьте в этот чат ссылку на видео с YouTube, Twitch (клипы), VK Видео или Rutube, чтобы предложить его для просмотра!\n\
+            Можно отправить сразу несколько ссылок или переслать пост с ними.\n\
+            Ваши предложения и их статус: /my\n\
+            Проверить любое видео: /status ссылка\n\
+            Что сейчас в очереди: /queue\n\
+            Поддержать чужие предложения: /vote\n\
+            Узнавать, когда их посмотрят: /notify\n\
+            Поделиться видео из очереди в другом чате: @{} название",
+            user.full_name(), me.username()
         )).await?;
     Ok(())
 }
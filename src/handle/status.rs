@@ -0,0 +1,79 @@
+use std::sync::Arc;
+
+use sea_orm::prelude::*;
+use teloxide::{prelude::*, types::ParseMode, utils::html};
+
+use database::{actions, archived, requests, sea_orm_active_enums::Status, videos};
+use crate::{queue::{self, Queue}, AppState};
+
+/// /status <ссылка>
+pub async fn command(bot: Bot, msg: Message, uid: UserId, state: Arc<AppState>, link: String) -> anyhow::Result<()> {
+    let Some(link) = youtube::parse_video_link(link.trim()) else {
+        bot.send_message(msg.chat.id, "После команды необходимо указать ссылку на видео. (/status https://youtu.be/...)").await?;
+        return Ok(());
+    };
+    let Some(video) = videos::Entity::find_by_id((link.id, link.platform.into())).one(&state.db).await? else {
+        bot.send_message(msg.chat.id, "Это видео ещё никто не предлагал.").await?;
+        return Ok(());
+    };
+
+    let mut text = format!("<b>{}</b>\n", html::escape(&video.title));
+    let request = video.find_related(requests::Entity).one(&state.db).await?;
+    // Скрытый запрос видит только тот, кто его предложил
+    let request = match request {
+        Some(request) if request.hidden && !contributed(&request, uid, &state).await? => None,
+        request => request,
+    };
+    if video.banned {
+        text.push_str("⛔ Видео в чёрном списке.");
+    } else if let Some(request) = request {
+        if let Some(viewed_at) = request.viewed_at {
+            text.push_str(&format!("👀 Просмотрено {}.", viewed_at.format("%d.%m.%Y")));
+        } else if request.status == Status::Unavailable {
+            text.push_str("🚫 Видео стало недоступно.");
+        } else {
+            text.push_str("🕒 Ждёт просмотра.\n");
+            text.push_str(&queue_text(&request, uid, &state).await?);
+        }
+    } else {
+        let shown = archived::Entity::find()
+            .filter(archived::Column::Platform.eq(video.platform.clone()))
+            .filter(archived::Column::Ytid.eq(video.ytid.clone()))
+            .filter(archived::Column::ViewedAt.is_not_null())
+            .count(&state.db).await?;
+        if shown != 0 {
+            text.push_str(&format!("⭐ Уже показывали ({shown} раз), но его можно предложить снова."));
+        } else {
+            text.push_str("Сейчас этого видео нет в очереди.");
+        }
+    }
+    bot.send_message(msg.chat.id, text).parse_mode(ParseMode::Html).await?;
+    Ok(())
+}
+
+/// Место в очереди, сколько ещё людей хотят посмотреть видео и когда примерно до него дойдут.
+pub async fn queue_text(request: &requests::Model, uid: UserId, state: &AppState) -> anyhow::Result<String> {
+    let queue = Queue::load(&[request.id], state).await?;
+    let Some(position) = queue.position(request.id) else {
+        return Ok(String::new());
+    };
+    let mut text = format!("Место в очереди: №{position}");
+    let others = request.find_related(actions::Entity)
+        .filter(actions::Column::Uid.ne(uid.0 as i64))
+        .all(&state.db).await?
+        .iter().filter(|action| queue::counts(request, action))
+        .count();
+    if others != 0 {
+        text.push_str(&format!("\nТакже хотят посмотреть: {others}"));
+    }
+    if let Some(eta) = queue::eta(position, state).await? {
+        text.push_str(&format!("\nОриентировочно: {}", eta.format("%d.%m")));
+    }
+    Ok(text)
+}
+
+pub async fn contributed(request: &requests::Model, uid: UserId, state: &AppState) -> anyhow::Result<bool> {
+    Ok(request.find_related(actions::Entity)
+        .filter(actions::Column::Uid.eq(uid.0 as i64))
+        .count(&state.db).await? != 0)
+}
@@ -0,0 +1,113 @@
+use std::sync::Arc;
+
+use sea_orm::prelude::*;
+use teloxide::{prelude::*, types::{InlineKeyboardButton, InlineKeyboardMarkup, ParseMode}, utils::html};
+
+use database::{requests, videos};
+use crate::{markup, queue::Queue, AppState};
+use super::add;
+
+/// Записей на одной странице.
+const PAGE_SIZE: usize = 8;
+/// Префикс параметра /start для голоса по ссылке из inline-режима.
+const START_UPVOTE: &str = "upvote_";
+
+/// Очередь непросмотренных с кнопками "+1".
+pub async fn command(bot: Bot, msg: Message, state: Arc<AppState>) -> anyhow::Result<()> {
+    let (text, keyboard) = render(0, &state).await?;
+    bot.send_message(msg.chat.id, text).parse_mode(ParseMode::Html).reply_markup(keyboard).await?;
+    Ok(())
+}
+
+/// Переключение страниц.
+pub async fn inline(bot: Bot, q: CallbackQuery, state: Arc<AppState>, page: u32) -> anyhow::Result<()> {
+    bot.answer_callback_query(&q.id).await?;
+    redraw(&bot, &q, page, &state).await
+}
+
+/// Голос за запрос и обновление страницы, с которой он отдан.
+pub async fn upvote_inline(bot: Bot, q: CallbackQuery, state: Arc<AppState>, (rid, page): (i32, u32)) -> anyhow::Result<()> {
+    let text = match add::upvote(&bot, rid, q.from.id, &state).await? {
+        Ok(video) => format!("Ваш голос за «{}» учтён!", video.title),
+        Err(reason) => reason,
+    };
+    // Текст уведомления ограничен 200 символами
+    bot.answer_callback_query(&q.id).text(markup::truncate(&text, 200)).show_alert(true).await?;
+    redraw(&bot, &q, page, &state).await
+}
+
+/// Голос по ссылке `t.me/<бот>?start=upvote_<rid>` из карточки, отправленной через inline-режим.
+pub async fn start(bot: Bot, msg: Message, uid: UserId, state: Arc<AppState>, rid: i32) -> anyhow::Result<()> {
+    let text = match add::upvote(&bot, rid, uid, &state).await? {
+        Ok(video) => format!("Ваш голос за «{}» учтён!\nЧто ещё есть в очереди: /queue", video.title),
+        Err(reason) => reason,
+    };
+    bot.send_message(msg.chat.id, text).await?;
+    Ok(())
+}
+
+/// Ссылка, открывающая бота с голосом за запрос.
+pub fn upvote_link(username: &str, rid: i32) -> String {
+    format!("https://t.me/{username}?start={START_UPVOTE}{rid}")
+}
+
+/// ID запроса из параметра /start, если это голос по ссылке.
+pub fn parse_start(payload: &str) -> Option<i32> {
+    payload.trim().strip_prefix(START_UPVOTE)?.parse().ok()
+}
+
+async fn redraw(bot: &Bot, q: &CallbackQuery, page: u32, state: &AppState) -> anyhow::Result<()> {
+    let (text, keyboard) = render(page, state).await?;
+    if let Some(message) = q.regular_message() {
+        bot.edit_message_text(message.chat.id, message.id, text).parse_mode(ParseMode::Html).reply_markup(keyboard).await?;
+    }
+    Ok(())
+}
+
+async fn render(page: u32, state: &AppState) -> anyhow::Result<(String, InlineKeyboardMarkup)> {
+    let queue = Queue::load(&[], state).await?;
+    if queue.is_empty() {
+        return Ok(("Очередь пуста, самое время что-нибудь предложить!".to_string(), InlineKeyboardMarkup::default()));
+    }
+    let (range, pages) = markup::page_bounds(queue.len(), page as usize, PAGE_SIZE);
+    let page = range.start / PAGE_SIZE;
+    let mut text = format!("Очередь ({}/{pages}), голосуйте за то, что хотите увидеть:", page + 1);
+    let mut keyboard = Vec::new();
+    for (index, (rid, contributors)) in queue.iter().enumerate().skip(range.start).take(range.len()) {
+        let Some(video) = find_video(rid, state).await? else { continue };
+        text.push_str(&format!("\n{}. <b>{}</b> 🙍‍♂️{contributors}", index + 1, html::escape(&video.title)));
+        keyboard.push(vec![InlineKeyboardButton::callback(
+            format!("+1 {}. {}", index + 1, markup::truncate(&video.title, 40)),
+            format!("upvote {rid} {page}"),
+        )]);
+    }
+    keyboard.push(markup::pager("vote", page, pages));
+    Ok((text, InlineKeyboardMarkup::new(keyboard)))
+}
+
+async fn find_video(rid: i32, state: &AppState) -> anyhow::Result<Option<videos::Model>> {
+    let Some(request) = requests::Entity::find_by_id(rid).one(&state.db).await? else {
+        return Ok(None);
+    };
+    Ok(request.find_related(videos::Entity).one(&state.db).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upvote_link() {
+        let link = upvote_link("doggy_bot", 42);
+        assert_eq!(link, "https://t.me/doggy_bot?start=upvote_42");
+        assert_eq!(parse_start(link.split_once("start=").unwrap().1), Some(42));
+    }
+
+    #[test]
+    fn test_parse_start() {
+        assert_eq!(parse_start(""), None);
+        assert_eq!(parse_start("upvote_"), None);
+        assert_eq!(parse_start("upvote_abc"), None);
+        assert_eq!(parse_start("info_42"), None);
+    }
+}
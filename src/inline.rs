@@ -4,6 +4,20 @@ pub enum InlineCommand {
     Pardon(i32),
     View(i32),
     Unview(i32),
+    BanChannel(i32),
+    PardonChannel(i32),
+    Approve(i32),
+    BanUser(i32),
+    UnbanUser(i32),
+    Promote(i32),
+    Purge(i32),
+    My(u32),
+    /// Отзыв предложения и страница /my, с которой он сделан
+    Withdraw(i32, Option<u32>),
+    Vote(u32),
+    Queue(u32),
+    /// Голос за запрос и страница очереди, с которой он отдан
+    Upvote(i32, u32),
     ArchiveViewed,
     ArchiveAll,
     ListUnviewed,
@@ -18,6 +32,21 @@ impl InlineCommand {
             "pardon" => Self::Pardon(parts.next()?.parse().ok()?),
             "view" => Self::View(parts.next()?.parse().ok()?),
             "unview" => Self::Unview(parts.next()?.parse().ok()?),
+            "ban_channel" => Self::BanChannel(parts.next()?.parse().ok()?),
+            "pardon_channel" => Self::PardonChannel(parts.next()?.parse().ok()?),
+            "approve" => Self::Approve(parts.next()?.parse().ok()?),
+            "ban_user" => Self::BanUser(parts.next()?.parse().ok()?),
+            "unban_user" => Self::UnbanUser(parts.next()?.parse().ok()?),
+            "promote" => Self::Promote(parts.next()?.parse().ok()?),
+            "purge" => Self::Purge(parts.next()?.parse().ok()?),
+            "my" => Self::My(parts.next()?.parse().ok()?),
+            "vote" => Self::Vote(parts.next()?.parse().ok()?),
+            "queue" => Self::Queue(parts.next()?.parse().ok()?),
+            "upvote" => Self::Upvote(parts.next()?.parse().ok()?, parts.next()?.parse().ok()?),
+            "withdraw" => Self::Withdraw(parts.next()?.parse().ok()?, match parts.next() {
+                Some(page) => Some(page.parse().ok()?),
+                None => None,
+            }),
             "archive_viewed" => Self::ArchiveViewed,
             "archive_all" => Self::ArchiveAll,
             "list_unviewed" => Self::ListUnviewed,
@@ -44,4 +73,51 @@ mod tests {
         let result = InlineCommand::parse(text);
         assert_eq!(result, Some(InlineCommand::Ban(123)));
     }
+
+    #[test]
+    fn test_parse_ban_channel() {
+        assert_eq!(InlineCommand::parse("ban_channel 7"), Some(InlineCommand::BanChannel(7)));
+        assert_eq!(InlineCommand::parse("pardon_channel 7"), Some(InlineCommand::PardonChannel(7)));
+        assert_eq!(InlineCommand::parse("ban_channel"), None);
+    }
+
+    #[test]
+    fn test_parse_ban_user() {
+        assert_eq!(InlineCommand::parse("ban_user 7"), Some(InlineCommand::BanUser(7)));
+        assert_eq!(InlineCommand::parse("unban_user 7"), Some(InlineCommand::UnbanUser(7)));
+        assert_eq!(InlineCommand::parse("ban_user x"), None);
+    }
+
+    #[test]
+    fn test_parse_shadow() {
+        assert_eq!(InlineCommand::parse("promote 7"), Some(InlineCommand::Promote(7)));
+        assert_eq!(InlineCommand::parse("purge 7"), Some(InlineCommand::Purge(7)));
+        assert_eq!(InlineCommand::parse("purge"), None);
+    }
+
+    #[test]
+    fn test_parse_my() {
+        assert_eq!(InlineCommand::parse("my 2"), Some(InlineCommand::My(2)));
+        assert_eq!(InlineCommand::parse("my -1"), None);
+    }
+
+    #[test]
+    fn test_parse_withdraw() {
+        assert_eq!(InlineCommand::parse("withdraw 5"), Some(InlineCommand::Withdraw(5, None)));
+        assert_eq!(InlineCommand::parse("withdraw 5 2"), Some(InlineCommand::Withdraw(5, Some(2))));
+        assert_eq!(InlineCommand::parse("withdraw 5 x"), None);
+    }
+
+    #[test]
+    fn test_parse_vote() {
+        assert_eq!(InlineCommand::parse("vote 1"), Some(InlineCommand::Vote(1)));
+        assert_eq!(InlineCommand::parse("upvote 5 1"), Some(InlineCommand::Upvote(5, 1)));
+        assert_eq!(InlineCommand::parse("upvote 5"), None);
+    }
+
+    #[test]
+    fn test_parse_queue() {
+        assert_eq!(InlineCommand::parse("queue 3"), Some(InlineCommand::Queue(3)));
+        assert_eq!(InlineCommand::parse("queue"), None);
+    }
 }
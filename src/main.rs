@@ -1,25 +1,34 @@
 use std::{env::var, sync::Arc, time::Duration};
 
-use dashmap::DashMap;
 use database::moderators;
 use migration::{Migrator, MigratorTrait};
+use chrono::Local;
 use sea_orm::{prelude::*, sea_query::OnConflict, ConnectOptions, Database, Set};
 use teloxide::{
     dispatching::dialogue::InMemStorage,
     macros::BotCommands, prelude::*, types::User
 };
-use tokio::time::Instant;
 use tracing_panic::panic_hook;
 use lazy_static::lazy_static;
 
 mod handle;
+mod digest;
 mod markup;
+mod metadata;
+mod quota;
+mod queue;
+mod revalidate;
 
 mod inline;
 pub use inline::InlineCommand;
 use url::Url;
+use metadata::MetadataCache;
+use quota::Quota;
+use youtube::{FakeProvider, InvidiousProvider, OEmbedProvider, OpenGraphProvider, Platform, Providers, Retry, RutubeProvider, SearchProvider, SearchResult, VideoMetadata, WatchPageProvider, WithDuration, YouTubeSearchProvider};
 
-pub const COOLDOWN_DURATION: Duration = Duration::from_secs(10);
+pub const METADATA_ATTEMPTS: u32 = 3;
+pub const METADATA_RETRY_DELAY: Duration = Duration::from_millis(500);
+pub const METADATA_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 lazy_static! {
@@ -48,6 +57,66 @@ lazy_static! {
     pub static ref CHANNEL_INVITE_HASH: Option<String> = {
         var("CHANNEL_INVITE_HASH").ok()
     };
+    pub static ref METADATA_PROVIDER: String = {
+        var("METADATA_PROVIDER").unwrap_or(String::from("oembed"))
+    };
+    pub static ref OEMBED_URL: Url = {
+        match var("OEMBED_URL") {
+            Ok(url) => url.parse().expect("Can't parse OEMBED_URL"),
+            Err(_) => youtube::DEFAULT_OEMBED_URL.parse().expect("Failed to parse default oEmbed url")
+        }
+    };
+    /// Страница просмотра, с которой берётся длительность при METADATA_PROVIDER=oembed
+    pub static ref WATCH_URL: Url = {
+        match var("WATCH_URL") {
+            Ok(url) => url.parse().expect("Can't parse WATCH_URL"),
+            Err(_) => youtube::DEFAULT_WATCH_URL.parse().expect("Failed to parse default watch url")
+        }
+    };
+    /// Как часто рассылать предлагавшим новости об их видео
+    pub static ref DIGEST_INTERVAL: Duration = {
+        Duration::from_secs(60 * var("DIGEST_INTERVAL").ok()
+            .map(|minutes| minutes.parse::<u64>().expect("Can't parse DIGEST_INTERVAL to u64.").max(1))
+            .unwrap_or(10))
+    };
+    pub static ref REVALIDATE_INTERVAL: Duration = {
+        Duration::from_secs(60 * var("REVALIDATE_INTERVAL").ok()
+            .map(|minutes| minutes.parse().expect("Can't parse REVALIDATE_INTERVAL to u64."))
+            .unwrap_or(360))
+    };
+    /// Максимальная длительность видео в секундах
+    pub static ref MAX_DURATION: Option<u32> = {
+        var("MAX_DURATION").ok().map(|minutes| minutes.parse::<u32>().expect("Can't parse MAX_DURATION to u32.")
+            .checked_mul(60).expect("MAX_DURATION is too large."))
+    };
+    /// Минимальная длительность видео в секундах
+    pub static ref MIN_DURATION: Option<u32> = {
+        var("MIN_DURATION").ok().map(|minutes| minutes.parse::<u32>().expect("Can't parse MIN_DURATION to u32.")
+            .checked_mul(60).expect("MIN_DURATION is too large."))
+    };
+    /// Сколько результатов показывать при поиске по названию, 0 отключает поиск
+    pub static ref SEARCH_RESULTS: usize = {
+        var("SEARCH_RESULTS").ok()
+            .map(|count| count.parse().expect("Can't parse SEARCH_RESULTS to usize."))
+            .unwrap_or(5)
+    };
+    /// Показывать ли в /queue, кто предложил видео
+    pub static ref QUEUE_SHOW_AUTHORS: bool = {
+        var("QUEUE_SHOW_AUTHORS").ok()
+            .map(|show| show.parse().expect("Can't parse QUEUE_SHOW_AUTHORS to bool."))
+            .unwrap_or(false)
+    };
+    /// Ограничения для обычных пользователей: QUOTA_MINUTE, QUOTA_HOUR, QUOTA_DAY, QUOTA_PENDING
+    pub static ref USER_QUOTA: Quota = {
+        Quota::from_env("QUOTA", Quota { per_minute: Some(3), per_hour: Some(20), per_day: Some(50), pending: None })
+    };
+    /// Ограничения для модераторов: MOD_QUOTA_MINUTE, MOD_QUOTA_HOUR, MOD_QUOTA_DAY, MOD_QUOTA_PENDING
+    pub static ref MODERATOR_QUOTA: Quota = {
+        Quota::from_env("MOD_QUOTA", Quota::default())
+    };
+    pub static ref INVIDIOUS_URL: Option<Url> = {
+        var("INVIDIOUS_URL").ok().map(|url| url.parse().expect("Can't parse INVIDIOUS_URL"))
+    };
 }
 
 
@@ -68,6 +137,7 @@ async fn main() -> anyhow::Result<()> {
 
     tracing::info!("Doggy-Watch v{VERSION}");
     tracing::info!("admins: {:?} tg api: {}", *ADMINISTRATORS, TELEGRAM_API_URL.as_str());
+    tracing::info!("metadata provider: {}", *METADATA_PROVIDER);
     let bot = Bot::new(&*TOKEN).set_api_url(TELEGRAM_API_URL.clone());
 
     let mut opt = ConnectOptions::new(&*DATABASE_URL);
@@ -94,11 +164,24 @@ async fn main() -> anyhow::Result<()> {
 
 
     // teloxide::repl(bot, answer).await;
-    let state = Arc::new(AppState {db, cooldown: DashMap::new()});
+    let state = Arc::new(AppState {
+        db,
+        metadata: MetadataCache::new(metadata_providers(), METADATA_CACHE_TTL),
+        search: search_provider(),
+    });
+
+    // Имя бота нужно для ссылок в карточках inline-режима
+    let me = bot.get_me().await?;
+
+    tokio::spawn(digest::run(bot.clone(), state.clone(), *DIGEST_INTERVAL));
+
+    if !REVALIDATE_INTERVAL.is_zero() {
+        tokio::spawn(revalidate::run(state.clone(), *REVALIDATE_INTERVAL));
+    }
     
     Dispatcher::builder(bot, handle::schema())
         // Pass the shared state to the handler as a dependency.
-        .dependencies(dptree::deps![state, InMemStorage::<DialogueState>::new()])
+        .dependencies(dptree::deps![state, InMemStorage::<DialogueState>::new(), me])
         .default_handler(|upd| async move {
             tracing::debug!("Unhandled update: {:?}", upd);
         })
@@ -116,21 +199,42 @@ pub enum DialogueState {
     #[default]
     Nothing,
     // User
-    AcceptVideo{ ytid: String, uid: u64, title: String },
+    AcceptVideo{ uid: u64, video: Candidate },
+    AcceptVideos{ uid: u64, videos: Vec<Candidate> },
+    PickVideo{ uid: u64, results: Vec<SearchResult> },
+    VideoNote{ uid: u64, video: Candidate },
     // Moderator
     NewModeratorInput,
     RemoveModeratorConfirm{ uid: String },
 }
 
+/// Видео ожидающее подтверждения пользователем.
+#[derive(Clone)]
+pub struct Candidate {
+    pub platform: Platform,
+    /// ID видео на платформе
+    pub ytid: String,
+    pub meta: VideoMetadata,
+    /// Начало и конец отрезка из ссылки, в секундах
+    pub start: Option<u32>,
+    pub end: Option<u32>,
+    /// Выбрано для добавления (при пакетном предложении)
+    pub selected: bool,
+    /// Помечено фильтрами для проверки модератором
+    pub flagged: bool,
+    /// Заметка предлагающего для модераторов
+    pub note: Option<String>,
+}
+
 #[derive(BotCommands, Clone)]
 #[command(rename_rule = "lowercase", description = "Список поддерживаемых команд:")]
 enum Command {
     #[command(description = "запустить бота и/или вывести этот текст.")]
-    Start,
+    Start(String),
     #[command(description = "вывести этот текст.")]
     Help,
-    #[command(description = "вывести список.")]
-    List,
+    #[command(description = "вывести список (/list 30 — только непросмотренные до 30 минут).")]
+    List(String),
     #[command(description = "действия с архивом.")]
     Archive,
     #[command(description = "вывести список модераторов.")]
@@ -139,8 +243,36 @@ enum Command {
     AddMod,
     #[command(description = "удалить модератора.")]
     RemMod(String),
+    #[command(description = "заблокировать канал.")]
+    BanChannel(String),
+    #[command(description = "разблокировать канал.")]
+    UnbanChannel(String),
+    #[command(description = "вывести заблокированные каналы.")]
+    Channels,
+    #[command(description = "заблокировать пользователя (/ban UID причина).")]
+    Ban(String),
+    #[command(description = "заблокировать пользователя на время (/tempban UID 7d причина).")]
+    TempBan(String),
+    #[command(description = "разблокировать пользователя.")]
+    Unban(String),
+    #[command(description = "заблокировать пользователя незаметно для него (/shadowban UID причина).")]
+    ShadowBan(String),
+    #[command(description = "вывести заблокированных пользователей.")]
+    Bans,
+    #[command(description = "вывести скрытые видео пользователей в теневом бане.")]
+    Shadow,
+    #[command(description = "правила фильтрации названий (/filter для справки).")]
+    Filter(String),
     #[command(description = "включить/выключить уведомления.")]
     Notify,
+    #[command(description = "ваши предложения и их судьба.")]
+    My,
+    #[command(description = "состояние видео по ссылке (/status ссылка).")]
+    Status(String),
+    #[command(description = "проголосовать за видео в очереди.")]
+    Vote,
+    #[command(description = "очередь непросмотренных видео.")]
+    Queue,
     About
 }
 
@@ -184,17 +316,59 @@ pub async fn cancel(bot: Bot, q: CallbackQuery, dialogue: MyDialogue) -> anyhow:
 
 struct AppState {
     db: DatabaseConnection,
-    cooldown: DashMap<u64, Instant>
-    
+    metadata: MetadataCache,
+    search: Box<dyn SearchProvider>,
+}
+
+/// Источники метаданных: YouTube по METADATA_PROVIDER, остальные платформы напрямую
+fn metadata_providers() -> Providers {
+    let providers = match METADATA_PROVIDER.as_str() {
+        "oembed" => Providers::new().with(Platform::YouTube, WithDuration::new(
+            Retry::new(OEmbedProvider::new(OEMBED_URL.clone()), METADATA_ATTEMPTS, METADATA_RETRY_DELAY),
+            Retry::new(WatchPageProvider::new(WATCH_URL.clone()), METADATA_ATTEMPTS, METADATA_RETRY_DELAY),
+        )),
+        "invidious" => Providers::new().with(Platform::YouTube, Retry::new(
+            InvidiousProvider::new(INVIDIOUS_URL.clone().expect("INVIDIOUS_URL env not set.")),
+            METADATA_ATTEMPTS, METADATA_RETRY_DELAY
+        )),
+        "fake" => {
+            tracing::warn!("Using fake metadata provider! Videos are not checked.");
+            return Platform::ALL.into_iter()
+                .fold(Providers::new(), |providers, platform| providers.with(platform, FakeProvider::permissive()));
+        },
+        other => panic!("Unknown METADATA_PROVIDER: {other}"),
+    };
+    providers
+        .with(Platform::Twitch, Retry::new(OpenGraphProvider::new(Platform::Twitch), METADATA_ATTEMPTS, METADATA_RETRY_DELAY))
+        .with(Platform::Vk, Retry::new(OpenGraphProvider::new(Platform::Vk), METADATA_ATTEMPTS, METADATA_RETRY_DELAY))
+        .with(Platform::Rutube, Retry::new(RutubeProvider::default(), METADATA_ATTEMPTS, METADATA_RETRY_DELAY))
+}
+
+/// Поиск по названию через тот же источник, что и метаданные YouTube
+fn search_provider() -> Box<dyn SearchProvider> {
+    match METADATA_PROVIDER.as_str() {
+        "invidious" => Box::new(Retry::new(
+            InvidiousProvider::new(INVIDIOUS_URL.clone().expect("INVIDIOUS_URL env not set.")),
+            METADATA_ATTEMPTS, METADATA_RETRY_DELAY
+        )),
+        "fake" => Box::new(FakeProvider::permissive()),
+        _ => Box::new(Retry::new(YouTubeSearchProvider::default(), METADATA_ATTEMPTS, METADATA_RETRY_DELAY)),
+    }
 }
 
 impl AppState {
     /// Возвращает Result<Rights> для переданного пользователя 
     async fn check_rights(&self, uid: &UserId) -> anyhow::Result<Rights> {
-        use database::moderators::Entity as Moderators;
+        use database::{banned_users, moderators::Entity as Moderators};
 
         Ok(if let Some(moder) = Moderators::find_by_id(uid.0 as i64).one(&self.db).await? {
             Rights::Moderator { can_add_mods: moder.can_add_mods }
+        } else if let Some(ban) = banned_users::Entity::find_by_id(uid.0 as i64)
+            // Теневой бан пользователю не показываем
+            .filter(banned_users::Column::Shadow.eq(false))
+            .filter(banned_users::Column::ExpiresAt.is_null().or(banned_users::Column::ExpiresAt.gt(Local::now().naive_local())))
+            .one(&self.db).await? {
+            Rights::Banned { reason: ban.reason, until: ban.expires_at }
         } else {
             Rights::None
         })
@@ -207,6 +381,10 @@ enum Rights {
     Moderator {
         can_add_mods: bool
     },
+    Banned {
+        reason: Option<String>,
+        until: Option<DateTime>,
+    },
 }
 
 /// Проверка подписки
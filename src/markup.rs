@@ -11,4 +11,70 @@ pub fn inline_cancel() -> InlineKeyboardMarkup {
         vec![InlineKeyboardButton::callback("Отменить", "cancel")]
     ];
     InlineKeyboardMarkup::new(keyboard)
-}
\ No newline at end of file
+}
+/// Подпись отрезка видео: "с 12:34" или "с 12:34 до 15:00".
+pub fn clip_label(start: Option<i32>, end: Option<i32>) -> Option<String> {
+    let clip = |secs: i32| youtube::format_duration(secs as u32);
+    match (start, end) {
+        (Some(start), Some(end)) => Some(format!("с {} до {}", clip(start), clip(end))),
+        (Some(start), None) => Some(format!("с {}", clip(start))),
+        (None, Some(end)) => Some(format!("до {}", clip(end))),
+        (None, None) => None,
+    }
+}
+
+/// Обрезает текст до `max` символов, добавляя многоточие.
+pub fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() > max {
+        format!("{}…", text.chars().take(max - 1).collect::<String>())
+    } else {
+        text.to_string()
+    }
+}
+
+/// Диапазон записей страницы и общее число страниц. Страница за концом списка сдвигается на последнюю.
+pub fn page_bounds(total: usize, page: usize, size: usize) -> (std::ops::Range<usize>, usize) {
+    let pages = total.div_ceil(size).max(1);
+    let start = page.min(pages - 1) * size;
+    (start..total.min(start + size), pages)
+}
+
+/// Кнопки "◀" и "▶" с данными вида "<command> <страница>".
+pub fn pager(command: &str, page: usize, pages: usize) -> Vec<InlineKeyboardButton> {
+    let mut row = Vec::new();
+    if page > 0 {
+        row.push(InlineKeyboardButton::callback("◀", format!("{command} {}", page - 1)));
+    }
+    if page + 1 < pages {
+        row.push(InlineKeyboardButton::callback("▶", format!("{command} {}", page + 1)));
+    }
+    row
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clip_label() {
+        assert_eq!(clip_label(None, None), None);
+        assert_eq!(clip_label(Some(754), None).as_deref(), Some("с 12:34"));
+        assert_eq!(clip_label(Some(754), Some(900)).as_deref(), Some("с 12:34 до 15:00"));
+        assert_eq!(clip_label(None, Some(60)).as_deref(), Some("до 1:00"));
+    }
+
+    #[test]
+    fn test_page_bounds() {
+        assert_eq!(page_bounds(0, 0, 10), (0..0, 1));
+        assert_eq!(page_bounds(7, 0, 10), (0..7, 1));
+        assert_eq!(page_bounds(25, 1, 10), (10..20, 3));
+        assert_eq!(page_bounds(25, 2, 10), (20..25, 3));
+        assert_eq!(page_bounds(25, 9, 10), (20..25, 3));
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("коротко", 10), "коротко");
+        assert_eq!(truncate("смотреть с 3:00", 8), "смотрет…");
+    }
+}
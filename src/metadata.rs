@@ -0,0 +1,179 @@
+use std::{sync::atomic::{AtomicU64, Ordering}, time::Duration};
+
+use dashmap::DashMap;
+use sea_orm::{DatabaseConnection, EntityTrait};
+use tokio::time::Instant;
+use youtube::{MetadataError, Platform, Providers, VideoMetadata};
+
+use database::videos;
+
+/// Сколько записей держать в памяти до чистки просроченных.
+const CLEANUP_THRESHOLD: usize = 1024;
+
+/// Кэш метаданных: сначала БД, затем память с TTL и только потом сеть.
+pub struct MetadataCache {
+    providers: Providers,
+    ttl: Duration,
+    entries: DashMap<(Platform, String), (Instant, VideoMetadata)>,
+    /// Нормализованная ссылка на канал -> каноническая (по ID)
+    channels: DashMap<String, String>,
+    db_hits: AtomicU64,
+    memory_hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheStats {
+    pub db_hits: u64,
+    pub memory_hits: u64,
+    pub misses: u64,
+}
+
+impl MetadataCache {
+    pub fn new(providers: Providers, ttl: Duration) -> Self {
+        Self {
+            providers,
+            ttl,
+            entries: DashMap::new(),
+            channels: DashMap::new(),
+            db_hits: AtomicU64::new(0),
+            memory_hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    pub async fn get(&self, platform: Platform, vid: &str, db: &DatabaseConnection) -> Result<VideoMetadata, MetadataError> {
+        match videos::Entity::find_by_id((vid.to_string(), platform.into())).one(db).await {
+            Ok(Some(video)) => {
+                self.db_hits.fetch_add(1, Ordering::Relaxed);
+                return Ok(VideoMetadata {
+                    title: video.title,
+                    author_name: video.channel,
+                    author_url: video.channel_url,
+                    thumbnail_url: video.thumbnail,
+                    duration: video.duration.map(|d| d as u32),
+                });
+            },
+            Ok(None) => (),
+            Err(err) => tracing::error!("Caused an exception in metadata cache lookup due: {err:?}"),
+        }
+        self.get_or_fetch(platform, vid).await
+    }
+
+    /// Минуя БД: память, затем сеть.
+    async fn get_or_fetch(&self, platform: Platform, vid: &str) -> Result<VideoMetadata, MetadataError> {
+        if let Some(entry) = self.entries.get(&(platform, vid.to_string())) {
+            let (fetched_at, metadata) = entry.value();
+            if fetched_at.elapsed() < self.ttl {
+                self.memory_hits.fetch_add(1, Ordering::Relaxed);
+                return Ok(metadata.clone());
+            }
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        self.refresh(platform, vid).await
+    }
+
+    /// Запрашивает метаданные из сети и обновляет кэш в памяти.
+    pub async fn refresh(&self, platform: Platform, vid: &str) -> Result<VideoMetadata, MetadataError> {
+        let metadata = self.providers.metadata(platform, vid).await?;
+        if self.entries.len() >= CLEANUP_THRESHOLD {
+            self.entries.retain(|_, (fetched_at, _)| fetched_at.elapsed() < self.ttl);
+        }
+        self.entries.insert((platform, vid.to_string()), (Instant::now(), metadata.clone()));
+        Ok(metadata)
+    }
+
+    /// Приводит ссылку на канал к виду `/channel/UC...`, чтобы бан по `@handle` совпадал
+    /// с видео, канал которого источник отдал по ID, и наоборот.
+    /// Если узнать ID не удалось, возвращает просто нормализованную ссылку.
+    pub async fn channel_url(&self, url: &str) -> Option<String> {
+        let normalized = youtube::normalize_channel_url(url)?;
+        if !youtube::needs_channel_id(&normalized) {
+            return Some(normalized);
+        }
+        if let Some(canonical) = self.channels.get(&normalized) {
+            return Some(canonical.clone());
+        }
+        match self.providers.channel_id(Platform::YouTube, &normalized).await {
+            Ok(Some(id)) => {
+                let canonical = youtube::channel_url(&id);
+                self.channels.insert(normalized, canonical.clone());
+                Some(canonical)
+            },
+            Ok(None) => Some(normalized),
+            Err(err) => {
+                tracing::warn!("Can't resolve channel ID of {normalized}: {err}");
+                Some(normalized)
+            },
+        }
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            db_hits: self.db_hits.load(Ordering::Relaxed),
+            memory_hits: self.memory_hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use youtube::FakeProvider;
+
+    const YT: Platform = Platform::YouTube;
+
+    use super::*;
+
+    fn cache(ttl: Duration) -> MetadataCache {
+        let meta = VideoMetadata { title: "Known".to_string(), ..Default::default() };
+        MetadataCache::new(Providers::new().with(YT, FakeProvider::new().with("VJFNcHgQ4HM", meta)), ttl)
+    }
+
+    #[tokio::test]
+    async fn test_memory_cache_hit() {
+        let cache = cache(Duration::from_secs(60));
+        assert_eq!(cache.get_or_fetch(YT, "VJFNcHgQ4HM").await.unwrap().title, "Known");
+        assert_eq!(cache.get_or_fetch(YT, "VJFNcHgQ4HM").await.unwrap().title, "Known");
+        assert_eq!(cache.stats(), CacheStats { db_hits: 0, memory_hits: 1, misses: 1 });
+    }
+
+    #[tokio::test]
+    async fn test_memory_cache_expires() {
+        let cache = cache(Duration::ZERO);
+        cache.get_or_fetch(YT, "VJFNcHgQ4HM").await.unwrap();
+        cache.get_or_fetch(YT, "VJFNcHgQ4HM").await.unwrap();
+        assert_eq!(cache.stats(), CacheStats { db_hits: 0, memory_hits: 0, misses: 2 });
+    }
+
+    #[tokio::test]
+    async fn test_errors_are_not_cached() {
+        let cache = cache(Duration::from_secs(60));
+        assert!(matches!(cache.get_or_fetch(YT, "rfDBTQNdj-M").await, Err(MetadataError::NotFound)));
+        assert!(matches!(cache.get_or_fetch(YT, "rfDBTQNdj-M").await, Err(MetadataError::NotFound)));
+        assert_eq!(cache.stats().misses, 2);
+    }
+
+    #[tokio::test]
+    async fn test_channel_handle_matches_id() {
+        let providers = Providers::new().with(YT, FakeProvider::new()
+            .with_channel("https://www.youtube.com/@doggydox", "UCX6OQ3DkcsbYNE6H8uQQuVA"));
+        let cache = MetadataCache::new(providers, Duration::from_secs(60));
+        // Бан по хэндлу и канал видео по ID дают одну и ту же ссылку
+        let banned = cache.channel_url("https://www.youtube.com/@DoggyDox").await.unwrap();
+        let video = cache.channel_url("https://www.youtube.com/channel/UCX6OQ3DkcsbYNE6H8uQQuVA").await.unwrap();
+        assert_eq!(banned, video);
+        assert_eq!(cache.channel_url("@doggydox").await.unwrap(), video);
+        // Неизвестный хэндл остаётся как есть
+        assert_eq!(cache.channel_url("@unknown").await.unwrap(), "https://www.youtube.com/@unknown");
+        assert_eq!(cache.channel_url("https://example.com/").await, None);
+    }
+
+    #[tokio::test]
+    async fn test_platforms_are_cached_separately() {
+        let cache = cache(Duration::from_secs(60));
+        cache.get_or_fetch(YT, "VJFNcHgQ4HM").await.unwrap();
+        assert!(matches!(cache.get_or_fetch(Platform::Rutube, "VJFNcHgQ4HM").await, Err(MetadataError::Unsupported)));
+        assert_eq!(cache.stats(), CacheStats { db_hits: 0, memory_hits: 0, misses: 2 });
+    }
+}
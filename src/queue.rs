@@ -0,0 +1,211 @@
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeDelta};
+use sea_orm::{prelude::*, Condition, JoinType, QuerySelect};
+
+use database::{actions, archived, requests, videos};
+use crate::AppState;
+
+/// Считается ли действие в числе участников. Вклад из теневого бана в видимый запрос не считается,
+/// а в скрытом запросе других и нет.
+pub fn counts(request: &requests::Model, action: &actions::Model) -> bool {
+    action.counted || request.hidden
+}
+
+/// Пауза между просмотрами, после которой считается, что начался новый стрим.
+const STREAM_GAP: TimeDelta = TimeDelta::hours(3);
+/// Сколько последних стримов учитывать в оценке.
+const RECENT_STREAMS: usize = 10;
+/// Насколько давнюю историю просмотров загружать.
+const HISTORY_DEPTH: TimeDelta = TimeDelta::days(90);
+
+/// Запрос в очереди непросмотренных.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Entry {
+    rid: i32,
+    /// День первого предложения
+    date: NaiveDate,
+    contributors: u64,
+}
+
+/// Очередь непросмотренных в том же порядке, что и в /list:
+/// сначала новые дни, внутри дня — с большим числом участников, так что голос только поднимает видео.
+pub struct Queue {
+    entries: Vec<Entry>,
+}
+
+impl Queue {
+    /// Загружает очередь. `include` — скрытые запросы, которые нужно показать их автору как обычные.
+    pub async fn load(include: &[i32], state: &AppState) -> anyhow::Result<Self> {
+        let mut visibility = Condition::any().add(requests::Column::Hidden.eq(false));
+        if !include.is_empty() {
+            visibility = visibility.add(requests::Column::Id.is_in(include.to_vec()));
+        }
+        let rows: Vec<(requests::Model, Vec<actions::Model>)> = requests::Entity::find()
+            .join(JoinType::InnerJoin, requests::Relation::Videos.def())
+            .filter(videos::Column::Banned.eq(false))
+            .filter(requests::Column::ViewedAt.is_null())
+            .filter(visibility)
+            .find_with_related(actions::Entity)
+            .all(&state.db).await?;
+        let entries = rows.into_iter().filter_map(|(request, actions)| {
+            let creator = actions.iter().min_by_key(|action| action.id)?;
+            let contributors = actions.iter().filter(|action| counts(&request, action)).count() as u64;
+            Some(Entry { rid: request.id, date: creator.created_at.date(), contributors })
+        }).collect();
+        Ok(Self::new(entries))
+    }
+
+    fn new(mut entries: Vec<Entry>) -> Self {
+        entries.sort_by(|a, b| b.date.cmp(&a.date)
+            .then(b.contributors.cmp(&a.contributors))
+            .then(a.rid.cmp(&b.rid)));
+        Self { entries }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// ID запросов и число участников по порядку очереди.
+    pub fn iter(&self) -> impl Iterator<Item = (i32, u64)> + '_ {
+        self.entries.iter().map(|entry| (entry.rid, entry.contributors))
+    }
+
+    /// Место запроса (с 1), если он в очереди.
+    pub fn position(&self, rid: i32) -> Option<u64> {
+        self.entries.iter().position(|entry| entry.rid == rid).map(|index| index as u64 + 1)
+    }
+}
+
+/// Ориентировочная дата просмотра видео на месте `position`.
+pub async fn eta(position: u64, state: &AppState) -> anyhow::Result<Option<NaiveDateTime>> {
+    let now = Local::now().naive_local();
+    let mut history: Vec<Option<NaiveDateTime>> = requests::Entity::find()
+        .select_only()
+        .column(requests::Column::ViewedAt)
+        .filter(requests::Column::ViewedAt.gt(now - HISTORY_DEPTH))
+        .into_tuple()
+        .all(&state.db).await?;
+    history.extend(archived::Entity::find()
+        .select_only()
+        .column(archived::Column::ViewedAt)
+        .filter(archived::Column::ViewedAt.gt(now - HISTORY_DEPTH))
+        .into_tuple::<Option<NaiveDateTime>>()
+        .all(&state.db).await?);
+    let history: Vec<NaiveDateTime> = history.into_iter().flatten().collect();
+    Ok(estimate(now, &history, position))
+}
+
+/// Делит историю просмотров на стримы и по среднему числу видео за стрим и
+/// среднему интервалу между стримами прикидывает, когда дойдёт очередь до `position`.
+fn estimate(now: NaiveDateTime, history: &[NaiveDateTime], position: u64) -> Option<NaiveDateTime> {
+    let mut history = history.to_vec();
+    history.sort_unstable();
+    // (начало стрима, просмотрено видео)
+    let mut streams: Vec<(NaiveDateTime, u64)> = Vec::new();
+    let mut last: Option<NaiveDateTime> = None;
+    for viewed_at in history {
+        match streams.last_mut() {
+            Some((_, count)) if last.is_some_and(|last| viewed_at - last <= STREAM_GAP) => *count += 1,
+            _ => streams.push((viewed_at, 1)),
+        }
+        last = Some(viewed_at);
+    }
+    let streams = &streams[streams.len().saturating_sub(RECENT_STREAMS)..];
+    if streams.len() < 2 {
+        return None;
+    }
+    let (first, _) = streams.first()?;
+    let (latest, _) = streams.last()?;
+    let interval = (*latest - *first) / (streams.len() as i32 - 1);
+    if interval <= TimeDelta::zero() {
+        return None;
+    }
+    let per_stream = streams.iter().map(|(_, count)| count).sum::<u64>().div_ceil(streams.len() as u64);
+    // Первый стрим, который ещё не начался
+    let mut next = *latest + interval;
+    while next <= now {
+        next += interval;
+    }
+    let waiting = i32::try_from(position.saturating_sub(1) / per_stream).ok()?;
+    Some(next + interval * waiting)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(d: u32, h: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 10, d).unwrap().and_hms_opt(h, 0, 0).unwrap()
+    }
+
+    fn entry(rid: i32, d: u32, contributors: u64) -> Entry {
+        Entry { rid, date: day(d, 0).date(), contributors }
+    }
+
+    #[test]
+    fn test_queue_order() {
+        let queue = Queue::new(vec![entry(1, 10, 1), entry(2, 12, 3), entry(3, 12, 1), entry(4, 11, 2)]);
+        assert_eq!(queue.position(2), Some(1));
+        assert_eq!(queue.position(3), Some(2));
+        assert_eq!(queue.position(4), Some(3));
+        assert_eq!(queue.position(1), Some(4));
+        assert_eq!(queue.position(5), None);
+    }
+
+    #[test]
+    fn test_upvote_never_lowers_rank() {
+        let entries = vec![entry(1, 10, 1), entry(2, 12, 3), entry(3, 12, 1), entry(4, 11, 2), entry(5, 12, 3), entry(6, 12, 2)];
+        let before = Queue::new(entries.clone());
+        for voted in &entries {
+            let after = Queue::new(entries.iter().cloned().map(|mut entry| {
+                if entry.rid == voted.rid {
+                    entry.contributors += 1;
+                }
+                entry
+            }).collect());
+            assert!(after.position(voted.rid) <= before.position(voted.rid), "upvote lowered rid {}", voted.rid);
+        }
+    }
+
+    #[test]
+    fn test_counts() {
+        let mut request = requests::Model {
+            id: 1,
+            ytid: "dQw4w9WgXcQ".to_string(),
+            viewed_at: None,
+            status: database::sea_orm_active_enums::Status::Available,
+            flagged: false,
+            start_at: None,
+            end_at: None,
+            platform: database::sea_orm_active_enums::Platform::Youtube,
+            hidden: false,
+        };
+        let mut action = actions::Model { id: 1, rid: 1, uid: 1, created_at: day(17, 12), note: None, counted: true };
+        assert!(counts(&request, &action));
+        action.counted = false;
+        assert!(!counts(&request, &action));
+        request.hidden = true;
+        assert!(counts(&request, &action));
+    }
+
+    #[test]
+    fn test_estimate_not_enough_history() {
+        assert_eq!(estimate(day(17, 12), &[], 1), None);
+        assert_eq!(estimate(day(17, 12), &[day(14, 20), day(14, 21)], 1), None);
+    }
+
+    #[test]
+    fn test_estimate() {
+        // Стримы по 2 видео 10, 12 и 14 числа, раз в двое суток
+        let history = [day(10, 20), day(10, 21), day(12, 20), day(12, 21), day(14, 20), day(14, 21)];
+        assert_eq!(estimate(day(15, 12), &history, 1), Some(day(16, 20)));
+        assert_eq!(estimate(day(15, 12), &history, 2), Some(day(16, 20)));
+        assert_eq!(estimate(day(15, 12), &history, 3), Some(day(18, 20)));
+        // Пропущенный стрим сдвигает оценку на следующий
+        assert_eq!(estimate(day(17, 12), &history, 1), Some(day(18, 20)));
+    }
+}
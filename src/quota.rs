@@ -0,0 +1,184 @@
+use std::{env::var, fmt};
+
+use chrono::{Local, NaiveDateTime, TimeDelta};
+use sea_orm::{prelude::*, QueryOrder, QuerySelect, Set};
+
+use database::{actions, requests, submissions};
+use crate::{AppState, Rights, MODERATOR_QUOTA, USER_QUOTA};
+
+/// Самое длинное окно, записи старше него больше не нужны.
+const LONGEST_WINDOW: TimeDelta = TimeDelta::days(1);
+
+/// Ограничения на предложение видео. `None` — без ограничения.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Quota {
+    pub per_minute: Option<u32>,
+    pub per_hour: Option<u32>,
+    pub per_day: Option<u32>,
+    /// Сколько непросмотренных запросов пользователь может иметь одновременно
+    pub pending: Option<u32>,
+}
+
+impl Quota {
+    /// Читает `<prefix>_MINUTE`, `<prefix>_HOUR`, `<prefix>_DAY` и `<prefix>_PENDING`.
+    /// Не заданная переменная берётся из `default`, `0` снимает ограничение.
+    pub fn from_env(prefix: &str, default: Quota) -> Self {
+        let read = |name: &str, default: Option<u32>| match var(format!("{prefix}_{name}")) {
+            Ok(value) => Some(value.parse::<u32>().unwrap_or_else(|_| panic!("Can't parse {prefix}_{name} to u32.")))
+                .filter(|&limit| limit != 0),
+            Err(_) => default,
+        };
+        Self {
+            per_minute: read("MINUTE", default.per_minute),
+            per_hour: read("HOUR", default.per_hour),
+            per_day: read("DAY", default.per_day),
+            pending: read("PENDING", default.pending),
+        }
+    }
+
+    fn windows(&self) -> impl Iterator<Item = (TimeDelta, u32)> {
+        [
+            (TimeDelta::minutes(1), self.per_minute),
+            (TimeDelta::hours(1), self.per_hour),
+            (LONGEST_WINDOW, self.per_day),
+        ].into_iter().filter_map(|(window, limit)| Some((window, limit?)))
+    }
+
+    /// Когда снова можно будет предложить видео, если лимит по времени исчерпан.
+    /// `history` — время предыдущих предложений за последние сутки.
+    pub fn available_at(&self, now: NaiveDateTime, history: &[NaiveDateTime]) -> Option<NaiveDateTime> {
+        let mut history = history.to_vec();
+        history.sort_unstable_by(|a, b| b.cmp(a));
+        self.windows().filter_map(|(window, limit)| {
+            // Лимит освободится, когда из окна выпадет limit-е с конца предложение
+            let blocking = history.get(limit.checked_sub(1)? as usize)?;
+            let free_at = *blocking + window;
+            (free_at > now).then_some(free_at)
+        }).max()
+    }
+}
+
+impl fmt::Display for Quota {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let limits: Vec<String> = [
+            (self.per_minute, "min"),
+            (self.per_hour, "hour"),
+            (self.per_day, "day"),
+            (self.pending, "pending"),
+        ].into_iter().filter_map(|(limit, unit)| Some(format!("{}/{unit}", limit?))).collect();
+        if limits.is_empty() {
+            f.write_str("unlimited")
+        } else {
+            f.write_str(&limits.join(", "))
+        }
+    }
+}
+
+/// Почему пользователь сейчас не может предложить видео.
+#[derive(Debug)]
+pub enum QuotaExceeded {
+    Rate(NaiveDateTime),
+    Pending(u32),
+}
+
+impl fmt::Display for QuotaExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuotaExceeded::Rate(at) => write!(f, "Слишком часто! Предложить следующее видео можно будет {}.", at.format("%d.%m в %H:%M:%S")),
+            QuotaExceeded::Pending(limit) => write!(f, "У вас уже {limit} непросмотренных видео в очереди. Дождитесь их просмотра!"),
+        }
+    }
+}
+
+impl std::error::Error for QuotaExceeded {}
+
+/// Ограничения, действующие для пользователя с такими правами.
+pub fn for_rights(rights: &Rights) -> &'static Quota {
+    match rights {
+        Rights::Moderator { .. } => &MODERATOR_QUOTA,
+        Rights::None | Rights::Banned { .. } => &USER_QUOTA,
+    }
+}
+
+/// Проверяет, может ли пользователь сейчас предложить ещё одно видео.
+pub async fn check(uid: u64, quota: &Quota, state: &AppState) -> anyhow::Result<Result<(), QuotaExceeded>> {
+    let now = Local::now().naive_local();
+    if quota.windows().next().is_some() {
+        let history: Vec<NaiveDateTime> = submissions::Entity::find()
+            .select_only()
+            .column(submissions::Column::CreatedAt)
+            .filter(submissions::Column::Uid.eq(uid as i64))
+            .filter(submissions::Column::CreatedAt.gt(now - LONGEST_WINDOW))
+            .order_by_desc(submissions::Column::CreatedAt)
+            .into_tuple()
+            .all(&state.db).await?;
+        if let Some(at) = quota.available_at(now, &history) {
+            return Ok(Err(QuotaExceeded::Rate(at)));
+        }
+    }
+    if let Some(limit) = quota.pending {
+        let pending = actions::Entity::find()
+            .inner_join(requests::Entity)
+            .filter(actions::Column::Uid.eq(uid as i64))
+            .filter(requests::Column::ViewedAt.is_null())
+            .count(&state.db).await?;
+        if pending >= limit as u64 {
+            return Ok(Err(QuotaExceeded::Pending(limit)));
+        }
+    }
+    Ok(Ok(()))
+}
+
+/// Записывает предложение пользователя и чистит записи, вышедшие за самое длинное окно.
+pub async fn record(uid: u64, state: &AppState) -> anyhow::Result<()> {
+    let now = Local::now().naive_local();
+    submissions::ActiveModel {
+        uid: Set(uid as i64),
+        created_at: Set(now),
+        ..Default::default()
+    }.insert(&state.db).await?;
+    submissions::Entity::delete_many()
+        .filter(submissions::Column::CreatedAt.lt(now - LONGEST_WINDOW))
+        .exec(&state.db).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    fn at(h: u32, m: u32, s: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 10, 17).unwrap().and_hms_opt(h, m, s).unwrap()
+    }
+
+    #[test]
+    fn test_available_at_unlimited() {
+        let history = [at(12, 0, 0), at(12, 0, 1), at(12, 0, 2)];
+        assert_eq!(Quota::default().available_at(at(12, 0, 3), &history), None);
+    }
+
+    #[test]
+    fn test_available_at_per_minute() {
+        let quota = Quota { per_minute: Some(2), ..Default::default() };
+        assert_eq!(quota.available_at(at(12, 0, 30), &[at(12, 0, 10)]), None);
+        // Второе из двух предложений за минуту исчерпывает лимит до выхода первого из окна
+        assert_eq!(quota.available_at(at(12, 0, 30), &[at(12, 0, 20), at(12, 0, 10)]), Some(at(12, 1, 10)));
+        assert_eq!(quota.available_at(at(12, 1, 10), &[at(12, 0, 20), at(12, 0, 10)]), None);
+    }
+
+    #[test]
+    fn test_available_at_strictest_window() {
+        let quota = Quota { per_minute: Some(5), per_hour: Some(3), ..Default::default() };
+        let history = [at(11, 30, 0), at(11, 50, 0), at(12, 0, 0)];
+        assert_eq!(quota.available_at(at(12, 0, 30), &history), Some(at(12, 30, 0)));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Quota::default().to_string(), "unlimited");
+        let quota = Quota { per_minute: Some(3), per_day: Some(50), pending: Some(10), ..Default::default() };
+        assert_eq!(quota.to_string(), "3/min, 50/day, 10/pending");
+    }
+}
@@ -0,0 +1,175 @@
+use std::{sync::Arc, time::Duration};
+
+use sea_orm::{prelude::*, IntoActiveModel, Set};
+use youtube::MetadataError;
+
+use database::{requests, sea_orm_active_enums::Status, videos};
+use crate::{metadata::MetadataCache, AppState};
+
+/// Пауза между запросами, чтобы не упереться в ограничения провайдера.
+const REQUEST_PAUSE: Duration = Duration::from_secs(1);
+
+/// Периодически перепроверяет доступность непросмотренных видео.
+pub async fn run(state: Arc<AppState>, period: Duration) {
+    // Первая проверка через period, а не сразу при запуске
+    let mut interval = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+    loop {
+        interval.tick().await;
+        match revalidate(&state).await {
+            Ok((unavailable, renamed)) => tracing::info!("Revalidation finished: {unavailable} unavailable, {renamed} renamed"),
+            Err(err) => tracing::error!("Caused an exception in revalidate due: {err:?}"),
+        }
+    }
+}
+
+/// Результат перепроверки одного видео.
+#[derive(Debug, PartialEq)]
+enum Check {
+    /// Видео доступно. Новое название и длительность, если они изменились или появились
+    Available { title: Option<String>, duration: Option<i32> },
+    Unavailable,
+    /// Временная ошибка, статус не трогаем
+    Unknown,
+}
+
+async fn check(video: &videos::Model, metadata: &MetadataCache) -> Check {
+    match metadata.refresh(video.platform.clone().into(), &video.ytid).await {
+        Ok(meta) => Check::Available {
+            title: Some(meta.title).filter(|title| *title != video.title),
+            duration: meta.duration
+                .and_then(|duration| i32::try_from(duration).ok())
+                .filter(|_| video.duration.is_none()),
+        },
+        Err(MetadataError::NotFound | MetadataError::Private | MetadataError::Unavailable | MetadataError::EmbeddingDisabled) => Check::Unavailable,
+        Err(err) => {
+            tracing::warn!("Can't revalidate {}: {err}", video.ytid);
+            Check::Unknown
+        },
+    }
+}
+
+/// Возвращает количество недоступных и переименованных видео.
+async fn revalidate(state: &AppState) -> anyhow::Result<(u32, u32)> {
+    let entities: Vec<(requests::Model, Option<videos::Model>)> = requests::Entity::find()
+        .find_also_related(videos::Entity)
+        .filter(requests::Column::ViewedAt.is_null())
+        .all(&state.db).await?;
+
+    let (mut unavailable, mut renamed) = (0, 0);
+    for (request, video) in entities {
+        let Some(video) = video else { continue };
+        let check = check(&video, &state.metadata).await;
+        match &check {
+            Check::Available { title: Some(title), .. } => {
+                tracing::info!("Video {} renamed: {:?} -> {:?}", video.ytid, video.title, title);
+                renamed += 1;
+            },
+            Check::Unavailable => unavailable += 1,
+            _ => (),
+        }
+        // Ошибка БД на одном видео не должна прерывать проверку остальных
+        let ytid = video.ytid.clone();
+        if let Err(err) = apply(request, video, check, state).await {
+            tracing::error!("Caused an exception in revalidate of {ytid} due: {err:?}");
+        }
+        tokio::time::sleep(REQUEST_PAUSE).await;
+    }
+    Ok((unavailable, renamed))
+}
+
+async fn apply(request: requests::Model, video: videos::Model, check: Check, state: &AppState) -> anyhow::Result<()> {
+    let status = match check {
+        Check::Available { title, duration } => {
+            if title.is_some() || duration.is_some() {
+                let mut video = video.into_active_model();
+                if let Some(title) = title {
+                    video.title = Set(title);
+                }
+                if let Some(duration) = duration {
+                    video.duration = Set(Some(duration));
+                }
+                video.update(&state.db).await?;
+            }
+            Status::Available
+        },
+        Check::Unavailable => Status::Unavailable,
+        Check::Unknown => return Ok(()),
+    };
+    if request.status != status {
+        let mut request = request.into_active_model();
+        request.status = Set(status);
+        request.update(&state.db).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use youtube::{FakeProvider, MetadataProvider, Platform, Providers, VideoMetadata};
+
+    use super::*;
+
+    /// Всегда отвечает одной и той же ошибкой.
+    struct Failing(fn() -> MetadataError);
+
+    #[async_trait::async_trait]
+    impl MetadataProvider for Failing {
+        async fn metadata(&self, _vid: &str) -> Result<VideoMetadata, MetadataError> {
+            Err((self.0)())
+        }
+    }
+
+    fn video(title: &str, duration: Option<i32>) -> videos::Model {
+        videos::Model {
+            ytid: "VJFNcHgQ4HM".to_string(),
+            title: title.to_string(),
+            banned: false,
+            channel: None,
+            channel_url: None,
+            thumbnail: None,
+            duration,
+            platform: Platform::YouTube.into(),
+        }
+    }
+
+    fn cache<P: MetadataProvider + 'static>(provider: P) -> MetadataCache {
+        MetadataCache::new(Providers::new().with(Platform::YouTube, provider), Duration::from_secs(60))
+    }
+
+    fn fake(title: &str, duration: Option<u32>) -> MetadataCache {
+        let meta = VideoMetadata { title: title.to_string(), duration, ..Default::default() };
+        cache(FakeProvider::new().with("VJFNcHgQ4HM", meta))
+    }
+
+    #[tokio::test]
+    async fn test_check_unchanged() {
+        let result = check(&video("Video", Some(754)), &fake("Video", Some(754))).await;
+        assert_eq!(result, Check::Available { title: None, duration: None });
+    }
+
+    #[tokio::test]
+    async fn test_check_renamed() {
+        let result = check(&video("Old", Some(754)), &fake("New", Some(754))).await;
+        assert_eq!(result, Check::Available { title: Some("New".to_string()), duration: None });
+    }
+
+    #[tokio::test]
+    async fn test_check_fills_duration() {
+        let result = check(&video("Video", None), &fake("Video", Some(754))).await;
+        assert_eq!(result, Check::Available { title: None, duration: Some(754) });
+        // Длительность, не влезающая в БД, не записывается
+        let result = check(&video("Video", None), &fake("Video", Some(u32::MAX))).await;
+        assert_eq!(result, Check::Available { title: None, duration: None });
+    }
+
+    #[tokio::test]
+    async fn test_check_unavailable() {
+        assert_eq!(check(&video("Video", None), &cache(FakeProvider::new())).await, Check::Unavailable);
+        assert_eq!(check(&video("Video", None), &cache(Failing(|| MetadataError::Private))).await, Check::Unavailable);
+    }
+
+    #[tokio::test]
+    async fn test_check_transient_error() {
+        assert_eq!(check(&video("Video", None), &cache(Failing(|| MetadataError::RateLimited))).await, Check::Unknown);
+    }
+}
@@ -0,0 +1,107 @@
+use reqwest::StatusCode;
+
+/// Причины по которым не удалось получить метаданные видео.
+#[derive(Debug, thiserror::Error)]
+pub enum MetadataError {
+    #[error("video not found")]
+    NotFound,
+    #[error("video is private")]
+    Private,
+    #[error("video was removed or is unavailable")]
+    Unavailable,
+    #[error("embedding is disabled for this video")]
+    EmbeddingDisabled,
+    #[error("rate limited by the metadata provider")]
+    RateLimited,
+    #[error("metadata provider responded with {0}")]
+    Server(StatusCode),
+    #[error("network error: {0}")]
+    Network(#[source] reqwest::Error),
+    #[error("invalid response: {0}")]
+    InvalidResponse(String),
+    #[error("platform is not supported")]
+    Unsupported,
+}
+
+impl MetadataError {
+    /// Имеет ли смысл повторить запрос.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::RateLimited | Self::Server(_) | Self::Network(_))
+    }
+
+    /// Ошибка по HTTP статусу ответа oEmbed.
+    pub fn from_status(status: StatusCode) -> Self {
+        match status {
+            StatusCode::BAD_REQUEST | StatusCode::NOT_FOUND => Self::NotFound,
+            StatusCode::UNAUTHORIZED => Self::EmbeddingDisabled,
+            StatusCode::FORBIDDEN => Self::Private,
+            StatusCode::GONE => Self::Unavailable,
+            StatusCode::TOO_MANY_REQUESTS => Self::RateLimited,
+            status => Self::Server(status),
+        }
+    }
+
+    /// Ошибка по тексту из ответа Invidious (`{"error": "..."}`).
+    pub fn from_message(message: &str) -> Option<Self> {
+        let message = message.to_lowercase();
+        Some(if message.contains("private") {
+            Self::Private
+        } else if message.contains("embed") {
+            Self::EmbeddingDisabled
+        } else if message.contains("removed") || message.contains("terminated") || message.contains("unavailable") {
+            Self::Unavailable
+        } else if message.contains("not found") || message.contains("does not exist") {
+            Self::NotFound
+        } else if message.contains("rate") || message.contains("too many") {
+            Self::RateLimited
+        } else {
+            return None;
+        })
+    }
+}
+
+impl From<reqwest::Error> for MetadataError {
+    fn from(err: reqwest::Error) -> Self {
+        if err.is_decode() {
+            Self::InvalidResponse(err.to_string())
+        } else if let Some(status) = err.status() {
+            Self::from_status(status)
+        } else {
+            Self::Network(err)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_status() {
+        assert!(matches!(MetadataError::from_status(StatusCode::BAD_REQUEST), MetadataError::NotFound));
+        assert!(matches!(MetadataError::from_status(StatusCode::UNAUTHORIZED), MetadataError::EmbeddingDisabled));
+        assert!(matches!(MetadataError::from_status(StatusCode::FORBIDDEN), MetadataError::Private));
+        assert!(matches!(MetadataError::from_status(StatusCode::NOT_FOUND), MetadataError::NotFound));
+        assert!(matches!(MetadataError::from_status(StatusCode::TOO_MANY_REQUESTS), MetadataError::RateLimited));
+        assert!(matches!(
+            MetadataError::from_status(StatusCode::INTERNAL_SERVER_ERROR),
+            MetadataError::Server(StatusCode::INTERNAL_SERVER_ERROR)
+        ));
+    }
+
+    #[test]
+    fn test_from_message() {
+        assert!(matches!(MetadataError::from_message("This video is private"), Some(MetadataError::Private)));
+        assert!(matches!(MetadataError::from_message("This video has been removed by the uploader"), Some(MetadataError::Unavailable)));
+        assert!(matches!(MetadataError::from_message("Video unavailable"), Some(MetadataError::Unavailable)));
+        assert!(MetadataError::from_message("Something went wrong").is_none());
+    }
+
+    #[test]
+    fn test_is_transient() {
+        assert!(MetadataError::RateLimited.is_transient());
+        assert!(MetadataError::Server(StatusCode::BAD_GATEWAY).is_transient());
+        assert!(!MetadataError::NotFound.is_transient());
+        assert!(!MetadataError::Private.is_transient());
+    }
+}
@@ -1,54 +1,234 @@
 use serde::{Deserialize, Serialize};
 use url::Url;
 
-#[derive(Debug, Serialize, Deserialize)]
+mod error;
+mod platform;
+mod provider;
+mod search;
+pub use error::MetadataError;
+pub use platform::*;
+pub use provider::*;
+pub use search::*;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct VideoMetadata {
     pub title: String,
+    /// Название канала
+    pub author_name: Option<String>,
+    /// Ссылка на канал
+    pub author_url: Option<String>,
+    pub thumbnail_url: Option<String>,
+    /// Длительность в секундах (oEmbed её не отдаёт)
+    #[serde(default)]
+    pub duration: Option<u32>,
 }
 
 pub const DEFAULT_YT: &str = "https://youtu.be/";
 
+/// Длина идентификатора YouTube видео.
+pub const VIDEO_ID_LEN: usize = 11;
+
+/// Хосты, на которых ссылки имеют вид `/watch?v=`, `/shorts/`, `/live/`, `/embed/` или `/v/`.
+const YOUTUBE_HOSTS: [&str; 4] = ["youtube.com", "m.youtube.com", "music.youtube.com", "youtube-nocookie.com"];
+
+/// Проверяет что строка является корректным ID видео (11 символов из `[A-Za-z0-9_-]`).
+pub fn is_valid_video_id(id: &str) -> bool {
+    id.len() == VIDEO_ID_LEN && id.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+}
+
+/// Ссылка на видео вместе с отрезком из параметров `t=`, `start=` и `end=`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VideoLink {
+    pub platform: Platform,
+    pub id: String,
+    /// Начало в секундах
+    pub start: Option<u32>,
+    /// Конец в секундах
+    pub end: Option<u32>,
+}
+
+/// Достаёт ID видео из любой известной формы YouTube ссылки.
+/// Ссылки без схемы (`youtu.be/...`) тоже принимаются.
 pub fn extract_youtube_video_id(url: &str) -> Option<String> {
-    if let Ok(parsed_url) = Url::parse(url) {
-        // Check if the URL is from YouTube or YouTube short URL
-        if parsed_url.host_str() == Some("youtube.com") || parsed_url.host_str() == Some("www.youtube.com") || parsed_url.host_str() == Some("music.youtube.com") {
-            // Extract the video ID from the query parameters
-            if let Some(query) = parsed_url.query() {
-                let params: std::collections::HashMap<_, _> = query.split('&')
-                    .filter_map(|param| {
-                        let (key, value) = param.split_once('=')?;
-                        Some((key, value))
-                    })
-                    .collect();
-                if let Some(video_id) = params.get("v") {
-                    return Some(video_id.to_string());
-                }
-            }
-        } else if parsed_url.host_str() == Some("youtu.be") {
-            // Extract the video ID from the path
-            if let Some(mut path) = parsed_url.path_segments() {
-                if let Some(video_id) = path.next() {
-                    return Some(video_id.to_string());
-                }
-            }
+    parse_youtube_link(url).map(|link| link.id)
+}
+
+/// Разбирает YouTube ссылку: ID видео и отрезок если он указан.
+pub fn parse_youtube_link(url: &str) -> Option<VideoLink> {
+    let url = url.trim();
+    let parsed_url = Url::parse(url)
+        .or_else(|_| Url::parse(&format!("https://{url}")))
+        .ok()?;
+    if !matches!(parsed_url.scheme(), "http" | "https") {
+        return None;
+    }
+
+    let host = parsed_url.host_str()?;
+    let host = host.strip_prefix("www.").unwrap_or(host);
+    let video_id = if host == "youtu.be" {
+        // Short URL: ID is the first path segment
+        parsed_url.path_segments()?.next()?.to_string()
+    } else if YOUTUBE_HOSTS.contains(&host) {
+        let mut path = parsed_url.path_segments()?;
+        match path.next()? {
+            "watch" => parsed_url.query_pairs()
+                .find_map(|(key, value)| (key == "v").then(|| value.into_owned()))?,
+            "shorts" | "live" | "embed" | "v" | "e" => path.next()?.to_string(),
+            _ => return None,
+        }
+    } else {
+        return None;
+    };
+    if !is_valid_video_id(&video_id) {
+        return None;
+    }
+
+    let (start, end) = clip_range(&parsed_url);
+    Some(VideoLink { platform: Platform::YouTube, id: video_id, start, end })
+}
+
+/// Отрезок из параметров ссылки `t=`, `start=` и `end=` (или из фрагмента `#t=`).
+fn clip_range(url: &Url) -> (Option<u32>, Option<u32>) {
+    let (mut start, mut end) = (None, None);
+    // Старые ссылки хранят время во фрагменте: #t=1m30s
+    let fragment = url.fragment().map(|fragment| url::form_urlencoded::parse(fragment.as_bytes()));
+    for (key, value) in url.query_pairs().chain(fragment.into_iter().flatten()) {
+        match key.as_ref() {
+            "t" | "start" => start = parse_timestamp(&value).or(start),
+            "end" => end = parse_timestamp(&value).or(end),
+            _ => (),
         }
     }
-    None
+    let start = start.filter(|&start| start != 0);
+    let end = end.filter(|&end| start.is_none_or(|start| end > start));
+    (start, end)
 }
 
-pub async fn get_video_metadata(vid: &str) -> Result<VideoMetadata, reqwest::Error> {
-    let response = reqwest::get(format!("https://www.youtube.com/oembed?url={DEFAULT_YT}{vid}")).await?;
+/// Разбирает отметку времени: `754`, `754s`, `12m34s`, `1h2m3s`.
+/// Отметки, не влезающие в `i32` (так они хранятся в БД), отбрасываются.
+pub fn parse_timestamp(value: &str) -> Option<u32> {
+    parse_seconds(value).filter(|&seconds| i32::try_from(seconds).is_ok())
+}
 
-    if !response.status().is_success() {
-        return Err(response.error_for_status().unwrap_err());
+fn parse_seconds(value: &str) -> Option<u32> {
+    if let Ok(seconds) = value.parse() {
+        return Some(seconds);
+    }
+    let mut total: u32 = 0;
+    let mut number = String::new();
+    for c in value.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let n: u32 = number.parse().ok()?;
+        number.clear();
+        total = total.checked_add(match c {
+            'h' => n.checked_mul(3600)?,
+            'm' => n.checked_mul(60)?,
+            's' => n,
+            _ => return None,
+        })?;
     }
+    (!value.is_empty() && number.is_empty()).then_some(total)
+}
+
+/// Ссылка на видео с началом воспроизведения.
+pub fn video_url(id: &str, start: Option<u32>) -> String {
+    match start {
+        Some(start) => format!("{DEFAULT_YT}{id}?t={start}"),
+        None => format!("{DEFAULT_YT}{id}"),
+    }
+}
+
+/// Приводит ссылку на канал к единому виду, чтобы их можно было сравнивать.
+/// Понимает `@handle`, `/channel/UC...`, `/c/name` и `/user/name`, а также каналы Rutube.
+pub fn normalize_channel_url(url: &str) -> Option<String> {
+    let url = url.trim();
+    let (kind, name) = if let Some(handle) = url.strip_prefix('@') {
+        ("@", handle.to_string())
+    } else {
+        let parsed_url = Url::parse(url)
+            .or_else(|_| Url::parse(&format!("https://{url}")))
+            .ok()?;
+        if !matches!(parsed_url.scheme(), "http" | "https") {
+            return None;
+        }
+        let host = parsed_url.host_str()?;
+        let host = host.strip_prefix("www.").unwrap_or(host);
+        if host == "rutube.ru" {
+            // Каналы Rutube: /channel/<число>/
+            let mut path = parsed_url.path_segments()?;
+            let id = path.next().filter(|&kind| kind == "channel").and(path.next())?;
+            let valid = !id.is_empty() && id.bytes().all(|b| b.is_ascii_digit());
+            return valid.then(|| format!("https://rutube.ru/channel/{id}/"));
+        }
+        if !YOUTUBE_HOSTS.contains(&host) {
+            return None;
+        }
+        let mut path = parsed_url.path_segments()?;
+        match path.next()? {
+            handle if handle.starts_with('@') => ("@", handle[1..].to_string()),
+            "channel" => ("channel/", path.next()?.to_string()),
+            "c" => ("c/", path.next()?.to_string()),
+            "user" => ("user/", path.next()?.to_string()),
+            _ => return None,
+        }
+    };
+    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.')) {
+        return None;
+    }
+    // ID каналов чувствительны к регистру, имена и хэндлы — нет
+    let name = if kind == "channel/" { name } else { name.to_lowercase() };
+    Some(format!("https://www.youtube.com/{kind}{name}"))
+}
+
+/// Похоже ли на ID YouTube канала: `UC` и 22 символа base64url.
+pub fn is_channel_id(id: &str) -> bool {
+    id.len() == 24 && id.starts_with("UC") && id.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+}
+
+/// Каноническая ссылка на YouTube канал по его ID.
+pub fn channel_url(id: &str) -> String {
+    format!("https://www.youtube.com/channel/{id}")
+}
 
-    let mut metadata: VideoMetadata = response.json().await?;
+/// Нормализованная ссылка указывает на YouTube канал не по ID (`@handle`, `/c/`, `/user/`),
+/// и для сравнения её нужно привести к [`channel_url`].
+pub fn needs_channel_id(normalized: &str) -> bool {
+    normalized.starts_with("https://www.youtube.com/") && !normalized.starts_with("https://www.youtube.com/channel/")
+}
+
+/// Отбрасывает отметки отрезка за концом видео известной длительности.
+pub fn fit_clip(start: Option<u32>, end: Option<u32>, duration: Option<u32>) -> (Option<u32>, Option<u32>) {
+    let fits = |offset: &u32| duration.is_none_or(|duration| *offset < duration);
+    (start.filter(fits), end.filter(fits))
+}
 
-    // ¯\_(ツ)_/¯
-    metadata.title = metadata.title.replace("/", "/ ");
+/// Ищет ссылки на видео любой поддерживаемой платформы в произвольном тексте.
+/// Повторы одного видео отбрасываются.
+pub fn find_video_links(text: &str) -> Vec<VideoLink> {
+    let mut links: Vec<VideoLink> = Vec::new();
+    for word in text.split_whitespace() {
+        // Ссылки часто обёрнуты в скобки/кавычки или стоят в конце предложения
+        let word = word.trim_matches(|c: char| matches!(c, '(' | ')' | '[' | ']' | '<' | '>' | '"' | '\'' | '«' | '»' | ',' | '.' | '!' | ';'));
+        if let Some(link) = parse_video_link(word) {
+            if !links.iter().any(|known| known.platform == link.platform && known.id == link.id) {
+                links.push(link);
+            }
+        }
+    }
+    links
+}
 
-    Ok(metadata)
+/// Форматирует длительность в секундах как `12:34` или `1:02:03`.
+pub fn format_duration(seconds: u32) -> String {
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours != 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes}:{seconds:02}")
+    }
 }
 
 #[cfg(test)]
@@ -90,4 +270,190 @@ mod tests {
         let url = "";
         assert_eq!(extract_youtube_video_id(url), None);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_extract_youtube_video_id_table() {
+        let cases: &[(&str, Option<&str>)] = &[
+            // watch
+            ("https://www.youtube.com/watch?v=VJFNcHgQ4HM", Some("VJFNcHgQ4HM")),
+            ("https://youtube.com/watch?feature=share&v=VJFNcHgQ4HM", Some("VJFNcHgQ4HM")),
+            ("http://m.youtube.com/watch?v=VJFNcHgQ4HM&t=42s", Some("VJFNcHgQ4HM")),
+            ("https://WWW.YouTube.com/watch?v=VJFNcHgQ4HM", Some("VJFNcHgQ4HM")),
+            ("youtube.com/watch?v=VJFNcHgQ4HM", Some("VJFNcHgQ4HM")),
+            // short
+            ("https://youtu.be/VJFNcHgQ4HM", Some("VJFNcHgQ4HM")),
+            ("youtu.be/VJFNcHgQ4HM?t=754", Some("VJFNcHgQ4HM")),
+            ("  https://youtu.be/VJFNcHgQ4HM  ", Some("VJFNcHgQ4HM")),
+            // paths
+            ("https://www.youtube.com/shorts/rfDBTQNdj-M", Some("rfDBTQNdj-M")),
+            ("https://youtube.com/shorts/rfDBTQNdj-M?si=abc", Some("rfDBTQNdj-M")),
+            ("https://www.youtube.com/live/rfDBTQNdj-M?feature=shared", Some("rfDBTQNdj-M")),
+            ("https://www.youtube.com/embed/rfDBTQNdj-M", Some("rfDBTQNdj-M")),
+            ("https://www.youtube-nocookie.com/embed/rfDBTQNdj-M?start=10", Some("rfDBTQNdj-M")),
+            ("https://www.youtube.com/v/rfDBTQNdj-M", Some("rfDBTQNdj-M")),
+            ("https://m.youtube.com/shorts/rfDBTQNdj-M", Some("rfDBTQNdj-M")),
+            ("https://music.youtube.com/watch?v=rfDBTQNdj-M", Some("rfDBTQNdj-M")),
+            // malformed IDs
+            ("https://youtu.be/VJFNcHgQ4H", None),
+            ("https://youtu.be/VJFNcHgQ4HMX", None),
+            ("https://www.youtube.com/watch?v=VJFNcHg%214HM", None),
+            ("https://www.youtube.com/watch?v=", None),
+            ("https://youtu.be/", None),
+            ("https://www.youtube.com/shorts/", None),
+            // not a video
+            ("https://www.youtube.com/@doggy_dox", None),
+            ("https://www.youtube.com/channel/UCxxxxxxxxxxxxxxxxxxxxxx", None),
+            ("https://www.youtube.com/playlist?list=RDCt2h5Xj41Ss", None),
+            ("https://notyoutube.com/watch?v=VJFNcHgQ4HM", None),
+            ("ftp://youtube.com/watch?v=VJFNcHgQ4HM", None),
+            ("VJFNcHgQ4HM", None),
+            ("просто текст", None),
+        ];
+        for (url, expected) in cases {
+            assert_eq!(
+                extract_youtube_video_id(url).as_deref(), *expected,
+                "unexpected result for {url:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_is_valid_video_id() {
+        assert!(is_valid_video_id("VJFNcHgQ4HM"));
+        assert!(is_valid_video_id("rfDBTQNdj-M"));
+        assert!(is_valid_video_id("___________"));
+        assert!(!is_valid_video_id(""));
+        assert!(!is_valid_video_id("VJFNcHgQ4H"));
+        assert!(!is_valid_video_id("VJFNcHgQ4HM1"));
+        assert!(!is_valid_video_id("VJFNcHg Q4H"));
+        assert!(!is_valid_video_id("ВидеоВидео1"));
+    }
+
+    #[test]
+    fn test_find_video_links_youtube() {
+        let cases: &[(&str, &[&str])] = &[
+            ("глянь это https://youtu.be/VJFNcHgQ4HM", &["VJFNcHgQ4HM"]),
+            ("(https://www.youtube.com/shorts/rfDBTQNdj-M), круто!", &["rfDBTQNdj-M"]),
+            ("«youtu.be/VJFNcHgQ4HM».", &["VJFNcHgQ4HM"]),
+            (
+                "https://youtu.be/VJFNcHgQ4HM\nhttps://www.youtube.com/watch?v=rfDBTQNdj-M\nhttps://youtu.be/VJFNcHgQ4HM?t=1",
+                &["VJFNcHgQ4HM", "rfDBTQNdj-M"],
+            ),
+            ("https://example.com/VJFNcHgQ4HM и ничего больше", &[]),
+            ("", &[]),
+        ];
+        for (text, expected) in cases {
+            let ids: Vec<String> = find_video_links(text).into_iter().map(|link| link.id).collect();
+            assert_eq!(ids, *expected, "unexpected result for {text:?}");
+        }
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(0), "0:00");
+        assert_eq!(format_duration(59), "0:59");
+        assert_eq!(format_duration(754), "12:34");
+        assert_eq!(format_duration(3723), "1:02:03");
+        assert_eq!(format_duration(36000), "10:00:00");
+    }
+
+    #[test]
+    fn test_video_metadata_from_oembed() {
+        let json = r#"{
+            "title": "Video",
+            "author_name": "Channel",
+            "author_url": "https://www.youtube.com/@channel",
+            "type": "video",
+            "thumbnail_url": "https://i.ytimg.com/vi/VJFNcHgQ4HM/hqdefault.jpg"
+        }"#;
+        let meta: VideoMetadata = serde_json::from_str(json).unwrap();
+        assert_eq!(meta, VideoMetadata {
+            title: "Video".to_string(),
+            author_name: Some("Channel".to_string()),
+            author_url: Some("https://www.youtube.com/@channel".to_string()),
+            thumbnail_url: Some("https://i.ytimg.com/vi/VJFNcHgQ4HM/hqdefault.jpg".to_string()),
+            duration: None,
+        });
+    }
+
+    #[test]
+    fn test_normalize_channel_url() {
+        let cases: &[(&str, Option<&str>)] = &[
+            ("https://www.youtube.com/@Doggy_Dox", Some("https://www.youtube.com/@doggy_dox")),
+            ("youtube.com/@doggy_dox/videos", Some("https://www.youtube.com/@doggy_dox")),
+            ("@Doggy_Dox", Some("https://www.youtube.com/@doggy_dox")),
+            ("https://m.youtube.com/channel/UCabcDEF123", Some("https://www.youtube.com/channel/UCabcDEF123")),
+            ("https://www.youtube.com/c/SomeName", Some("https://www.youtube.com/c/somename")),
+            ("https://www.youtube.com/user/SomeName", Some("https://www.youtube.com/user/somename")),
+            ("https://www.youtube.com/watch?v=VJFNcHgQ4HM", None),
+            ("https://www.youtube.com/channel/", None),
+            ("https://example.com/@doggy_dox", None),
+            ("https://rutube.ru/channel/23704195/videos/", Some("https://rutube.ru/channel/23704195/")),
+            ("https://rutube.ru/video/c6cc4d620b1d4338901770a44b3e82f4/", None),
+            ("@", None),
+            ("просто текст", None),
+        ];
+        for (url, expected) in cases {
+            assert_eq!(normalize_channel_url(url).as_deref(), *expected, "unexpected result for {url:?}");
+        }
+    }
+
+    #[test]
+    fn test_needs_channel_id() {
+        assert!(needs_channel_id("https://www.youtube.com/@doggy_dox"));
+        assert!(needs_channel_id("https://www.youtube.com/user/somename"));
+        assert!(!needs_channel_id(&channel_url("UCX6OQ3DkcsbYNE6H8uQQuVA")));
+        assert!(!needs_channel_id("https://rutube.ru/channel/23704195/"));
+        assert!(is_channel_id("UCX6OQ3DkcsbYNE6H8uQQuVA"));
+        assert!(!is_channel_id("UCabcDEF123"));
+        assert!(!is_channel_id("@doggy_dox"));
+    }
+
+    #[test]
+    fn test_fit_clip() {
+        assert_eq!(fit_clip(Some(60), Some(120), Some(754)), (Some(60), Some(120)));
+        assert_eq!(fit_clip(Some(60), Some(1000), Some(754)), (Some(60), None));
+        assert_eq!(fit_clip(Some(800), None, Some(754)), (None, None));
+        assert_eq!(fit_clip(Some(800), Some(900), None), (Some(800), Some(900)));
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(parse_timestamp("754"), Some(754));
+        assert_eq!(parse_timestamp("754s"), Some(754));
+        assert_eq!(parse_timestamp("12m34s"), Some(754));
+        assert_eq!(parse_timestamp("1h2m3s"), Some(3723));
+        assert_eq!(parse_timestamp("1h"), Some(3600));
+        assert_eq!(parse_timestamp(""), None);
+        assert_eq!(parse_timestamp("12m34"), None);
+        assert_eq!(parse_timestamp("abc"), None);
+        assert_eq!(parse_timestamp("3000000000"), None);
+        assert_eq!(parse_timestamp("600000h"), None);
+    }
+
+    #[test]
+    fn test_parse_youtube_link_offsets() {
+        let link = |id: &str, start, end| Some(VideoLink { platform: Platform::YouTube, id: id.to_string(), start, end });
+        let cases = [
+            ("https://youtu.be/VJFNcHgQ4HM?t=754", link("VJFNcHgQ4HM", Some(754), None)),
+            ("https://youtu.be/VJFNcHgQ4HM?si=abc&t=12m34s", link("VJFNcHgQ4HM", Some(754), None)),
+            ("https://www.youtube.com/watch?v=VJFNcHgQ4HM&t=1h2m3s", link("VJFNcHgQ4HM", Some(3723), None)),
+            ("https://www.youtube.com/watch?v=VJFNcHgQ4HM#t=90", link("VJFNcHgQ4HM", Some(90), None)),
+            ("https://www.youtube.com/embed/VJFNcHgQ4HM?start=10&end=70", link("VJFNcHgQ4HM", Some(10), Some(70))),
+            ("https://www.youtube.com/embed/VJFNcHgQ4HM?start=70&end=10", link("VJFNcHgQ4HM", Some(70), None)),
+            ("https://youtu.be/VJFNcHgQ4HM?t=0", link("VJFNcHgQ4HM", None, None)),
+            ("https://youtu.be/VJFNcHgQ4HM?t=abc", link("VJFNcHgQ4HM", None, None)),
+            ("https://youtu.be/VJFNcHgQ4HM", link("VJFNcHgQ4HM", None, None)),
+            ("https://example.com/watch?v=VJFNcHgQ4HM&t=10", None),
+        ];
+        for (url, expected) in cases {
+            assert_eq!(parse_youtube_link(url), expected, "unexpected result for {url:?}");
+        }
+    }
+
+    #[test]
+    fn test_video_url() {
+        assert_eq!(video_url("VJFNcHgQ4HM", None), "https://youtu.be/VJFNcHgQ4HM");
+        assert_eq!(video_url("VJFNcHgQ4HM", Some(754)), "https://youtu.be/VJFNcHgQ4HM?t=754");
+    }
+}
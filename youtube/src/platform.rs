@@ -0,0 +1,203 @@
+use std::{fmt, str::FromStr};
+
+use url::Url;
+
+use crate::{clip_range, parse_youtube_link, video_url, VideoLink, YOUTUBE_HOSTS};
+
+/// Максимальная длина ID видео на любой платформе (столбец `videos.ytid`).
+pub const MAX_EXTERNAL_ID_LEN: usize = 128;
+
+/// Видеоплатформы, ссылки на которые принимаются.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Platform {
+    YouTube,
+    /// Клипы Twitch
+    Twitch,
+    /// VK Видео
+    Vk,
+    Rutube,
+}
+
+impl Platform {
+    pub const ALL: [Platform; 4] = [Platform::YouTube, Platform::Twitch, Platform::Vk, Platform::Rutube];
+
+    /// Значение для хранения в БД.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Platform::YouTube => "youtube",
+            Platform::Twitch => "twitch",
+            Platform::Vk => "vk",
+            Platform::Rutube => "rutube",
+        }
+    }
+
+    /// Название для пользователей.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Platform::YouTube => "YouTube",
+            Platform::Twitch => "Twitch",
+            Platform::Vk => "VK Видео",
+            Platform::Rutube => "Rutube",
+        }
+    }
+
+    /// Короткая подпись для списков.
+    pub fn short_name(&self) -> &'static str {
+        match self {
+            Platform::YouTube => "YT",
+            Platform::Twitch => "TW",
+            Platform::Vk => "VK",
+            Platform::Rutube => "RT",
+        }
+    }
+
+    /// Ссылка на видео с началом воспроизведения (если платформа его поддерживает).
+    pub fn video_url(&self, id: &str, start: Option<u32>) -> String {
+        match (self, start) {
+            (Platform::YouTube, start) => video_url(id, start),
+            // Клипы короткие и отметки времени не поддерживают
+            (Platform::Twitch, _) => format!("https://clips.twitch.tv/{id}"),
+            (Platform::Vk, Some(start)) => format!("https://vkvideo.ru/video{id}?t={start}s"),
+            (Platform::Vk, None) => format!("https://vkvideo.ru/video{id}"),
+            (Platform::Rutube, Some(start)) => format!("https://rutube.ru/video/{id}/?t={start}"),
+            (Platform::Rutube, None) => format!("https://rutube.ru/video/{id}/"),
+        }
+    }
+}
+
+impl fmt::Display for Platform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Platform {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Platform::ALL.into_iter().find(|platform| platform.as_str() == s).ok_or(())
+    }
+}
+
+/// Разбирает ссылку на видео любой поддерживаемой платформы.
+/// Ссылки без схемы тоже принимаются.
+pub fn parse_video_link(url: &str) -> Option<VideoLink> {
+    let url = url.trim();
+    let parsed_url = Url::parse(url)
+        .or_else(|_| Url::parse(&format!("https://{url}")))
+        .ok()?;
+    if !matches!(parsed_url.scheme(), "http" | "https") {
+        return None;
+    }
+    let host = parsed_url.host_str()?;
+    let host = host.strip_prefix("www.").unwrap_or(host);
+    if host == "youtu.be" || YOUTUBE_HOSTS.contains(&host) {
+        return parse_youtube_link(url);
+    }
+    let host = host.strip_prefix("m.").unwrap_or(host);
+    let (platform, id) = match host {
+        "clips.twitch.tv" | "twitch.tv" => (Platform::Twitch, twitch_clip_id(&parsed_url)?),
+        "vk.com" | "vk.ru" | "vkvideo.ru" => (Platform::Vk, vk_video_id(&parsed_url)?),
+        "rutube.ru" => (Platform::Rutube, rutube_video_id(&parsed_url)?),
+        _ => return None,
+    };
+    let (start, end) = match platform {
+        Platform::Twitch => (None, None),
+        _ => clip_range(&parsed_url),
+    };
+    Some(VideoLink { platform, id, start, end })
+}
+
+/// `clips.twitch.tv/<slug>`, `twitch.tv/<канал>/clip/<slug>` и `clips.twitch.tv/embed?clip=<slug>`.
+fn twitch_clip_id(url: &Url) -> Option<String> {
+    let segments: Vec<&str> = url.path_segments()?.filter(|segment| !segment.is_empty()).collect();
+    let slug = match (url.host_str()?.ends_with("clips.twitch.tv"), segments.as_slice()) {
+        (true, ["embed"]) => url.query_pairs().find_map(|(key, value)| (key == "clip").then(|| value.into_owned()))?,
+        (true, [slug]) => slug.to_string(),
+        (false, [_, "clip", slug]) => slug.to_string(),
+        _ => return None,
+    };
+    let valid = !slug.is_empty() && slug.len() <= MAX_EXTERNAL_ID_LEN
+        && slug.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_');
+    valid.then_some(slug)
+}
+
+/// `vk.com/video-123_456`, `vk.com/clip-123_456`, `vkvideo.ru/video-123_456` и `vk.com/...?z=video-123_456`.
+/// ID хранится как `<владелец>_<видео>`: `-123_456`.
+fn vk_video_id(url: &Url) -> Option<String> {
+    let from_query = url.query_pairs()
+        .find_map(|(key, value)| (key == "z").then(|| value.split('/').next().unwrap_or_default().to_string()));
+    let candidates = url.path_segments().into_iter().flatten().map(str::to_string).chain(from_query);
+    candidates.filter_map(|segment| {
+        let id = segment.strip_prefix("video").or_else(|| segment.strip_prefix("clip"))?;
+        let (owner, video) = id.split_once('_')?;
+        let owner_digits = owner.strip_prefix('-').unwrap_or(owner);
+        let valid = !owner_digits.is_empty() && !video.is_empty() && id.len() <= MAX_EXTERNAL_ID_LEN
+            && owner_digits.bytes().all(|b| b.is_ascii_digit()) && video.bytes().all(|b| b.is_ascii_digit());
+        valid.then(|| id.to_string())
+    }).next()
+}
+
+/// `rutube.ru/video/<id>/`, `rutube.ru/shorts/<id>/` и `rutube.ru/play/embed/<id>`.
+fn rutube_video_id(url: &Url) -> Option<String> {
+    let segments: Vec<&str> = url.path_segments()?.filter(|segment| !segment.is_empty()).collect();
+    let id = match segments.as_slice() {
+        ["video" | "shorts", id, ..] => id,
+        ["play", "embed", id, ..] => id,
+        _ => return None,
+    };
+    let valid = id.len() == 32 && id.bytes().all(|b| b.is_ascii_hexdigit());
+    valid.then(|| id.to_ascii_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(platform: Platform, id: &str, start: Option<u32>) -> Option<VideoLink> {
+        Some(VideoLink { platform, id: id.to_string(), start, end: None })
+    }
+
+    #[test]
+    fn test_parse_video_link() {
+        let rutube_id = "c6cc4d620b1d4338901770a44b3e82f4";
+        let cases = [
+            ("https://youtu.be/VJFNcHgQ4HM?t=10", link(Platform::YouTube, "VJFNcHgQ4HM", Some(10))),
+            ("https://clips.twitch.tv/FunnyClipSlug-AbC_123", link(Platform::Twitch, "FunnyClipSlug-AbC_123", None)),
+            ("https://www.twitch.tv/streamer/clip/FunnyClipSlug?filter=clips", link(Platform::Twitch, "FunnyClipSlug", None)),
+            ("https://clips.twitch.tv/embed?clip=FunnyClipSlug&parent=example.com", link(Platform::Twitch, "FunnyClipSlug", None)),
+            ("https://m.twitch.tv/streamer/clip/FunnyClipSlug", link(Platform::Twitch, "FunnyClipSlug", None)),
+            ("https://www.twitch.tv/streamer", None),
+            ("https://vk.com/video-12345_67890", link(Platform::Vk, "-12345_67890", None)),
+            ("https://vkvideo.ru/video-12345_67890?t=1m30s", link(Platform::Vk, "-12345_67890", Some(90))),
+            ("https://m.vk.com/clip12345_67890", link(Platform::Vk, "12345_67890", None)),
+            ("https://vk.com/videos-12345?z=video-12345_67890%2Fpl_cat_trends", link(Platform::Vk, "-12345_67890", None)),
+            ("https://vk.com/durov", None),
+            (&format!("https://vk.com/video-1_{}", "9".repeat(MAX_EXTERNAL_ID_LEN)), None),
+            (&format!("https://rutube.ru/video/{rutube_id}/"), link(Platform::Rutube, rutube_id, None)),
+            (&format!("rutube.ru/video/{rutube_id}/?t=754"), link(Platform::Rutube, rutube_id, Some(754))),
+            (&format!("https://rutube.ru/play/embed/{rutube_id}"), link(Platform::Rutube, rutube_id, None)),
+            ("https://rutube.ru/video/short/", None),
+            ("https://example.com/video-12345_67890", None),
+        ];
+        for (url, expected) in cases {
+            assert_eq!(parse_video_link(url), expected, "unexpected result for {url:?}");
+        }
+    }
+
+    #[test]
+    fn test_platform_video_url() {
+        assert_eq!(Platform::YouTube.video_url("VJFNcHgQ4HM", Some(754)), "https://youtu.be/VJFNcHgQ4HM?t=754");
+        assert_eq!(Platform::Twitch.video_url("FunnyClipSlug", Some(10)), "https://clips.twitch.tv/FunnyClipSlug");
+        assert_eq!(Platform::Vk.video_url("-12345_67890", Some(90)), "https://vkvideo.ru/video-12345_67890?t=90s");
+        assert_eq!(Platform::Rutube.video_url("c6cc4d620b1d4338901770a44b3e82f4", None), "https://rutube.ru/video/c6cc4d620b1d4338901770a44b3e82f4/");
+    }
+
+    #[test]
+    fn test_platform_from_str() {
+        for platform in Platform::ALL {
+            assert_eq!(platform.as_str().parse(), Ok(platform));
+        }
+        assert_eq!("dailymotion".parse::<Platform>(), Err(()));
+    }
+}
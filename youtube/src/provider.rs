@@ -0,0 +1,858 @@
+use std::{collections::HashMap, future::Future, time::Duration};
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use url::Url;
+
+use crate::{MetadataError, Platform, SearchProvider, SearchResult, VideoMetadata, DEFAULT_YT};
+
+pub const DEFAULT_OEMBED_URL: &str = "https://www.youtube.com/oembed";
+pub const DEFAULT_WATCH_URL: &str = "https://www.youtube.com/watch";
+pub const DEFAULT_RUTUBE_API_URL: &str = "https://rutube.ru/api/";
+
+/// Источник метаданных видео.
+#[async_trait]
+pub trait MetadataProvider: Send + Sync {
+    async fn metadata(&self, vid: &str) -> Result<VideoMetadata, MetadataError>;
+
+    /// ID канала (`UC...`) по нормализованной ссылке на него.
+    /// `None` — источник не умеет определять ID.
+    async fn channel_id(&self, _url: &str) -> Result<Option<String>, MetadataError> {
+        Ok(None)
+    }
+}
+
+/// Общая обработка метаданных для всех источников.
+fn normalize(mut metadata: VideoMetadata) -> VideoMetadata {
+    // ¯\_(ツ)_/¯
+    metadata.title = metadata.title.replace("/", "/ ");
+    metadata
+}
+
+// ------------------------
+// oEmbed
+// ------------------------
+
+/// Официальный oEmbed YouTube (или совместимый с ним сервис).
+/// oEmbed не знает длительность, поэтому его стоит дополнять [`WatchPageProvider`] через [`WithDuration`].
+pub struct OEmbedProvider {
+    base_url: Url,
+    client: reqwest::Client,
+}
+
+impl OEmbedProvider {
+    pub fn new(base_url: Url) -> Self {
+        Self { base_url, client: reqwest::Client::new() }
+    }
+
+    fn request_url(&self, vid: &str) -> Url {
+        let mut url = self.base_url.clone();
+        url.query_pairs_mut()
+            .append_pair("url", &format!("{DEFAULT_YT}{vid}"))
+            .append_pair("format", "json");
+        url
+    }
+}
+
+impl Default for OEmbedProvider {
+    fn default() -> Self {
+        Self::new(DEFAULT_OEMBED_URL.parse().expect("Failed to parse default oEmbed url"))
+    }
+}
+
+#[async_trait]
+impl MetadataProvider for OEmbedProvider {
+    async fn metadata(&self, vid: &str) -> Result<VideoMetadata, MetadataError> {
+        let response = self.client.get(self.request_url(vid)).send().await?;
+        if !response.status().is_success() {
+            return Err(MetadataError::from_status(response.status()));
+        }
+        let metadata: VideoMetadata = response.json().await?;
+        Ok(normalize(metadata))
+    }
+}
+
+// ------------------------
+// Watch page
+// ------------------------
+
+/// Страница просмотра YouTube (или её зеркало): название из OpenGraph и длительность.
+pub struct WatchPageProvider {
+    watch_url: Url,
+    client: reqwest::Client,
+}
+
+impl WatchPageProvider {
+    pub fn new(watch_url: Url) -> Self {
+        Self { watch_url, client: reqwest::Client::new() }
+    }
+
+    fn request_url(&self, vid: &str) -> Url {
+        let mut url = self.watch_url.clone();
+        url.query_pairs_mut().append_pair("v", vid);
+        url
+    }
+}
+
+impl Default for WatchPageProvider {
+    fn default() -> Self {
+        Self::new(DEFAULT_WATCH_URL.parse().expect("Failed to parse default watch url"))
+    }
+}
+
+#[async_trait]
+impl MetadataProvider for WatchPageProvider {
+    async fn metadata(&self, vid: &str) -> Result<VideoMetadata, MetadataError> {
+        let response = self.client.get(self.request_url(vid))
+            .header(reqwest::header::ACCEPT_LANGUAGE, "en")
+            .send().await?;
+        if !response.status().is_success() {
+            return Err(MetadataError::from_status(response.status()));
+        }
+        let page = response.text().await?;
+        let mut metadata = extract_open_graph(&page).ok_or(MetadataError::NotFound)?;
+        metadata.duration = extract_page_duration(&page).or(metadata.duration);
+        Ok(normalize(metadata))
+    }
+
+    /// Страница канала лежит на том же сайте, что и страница просмотра.
+    async fn channel_id(&self, url: &str) -> Result<Option<String>, MetadataError> {
+        let path = Url::parse(url).map_err(|err| MetadataError::InvalidResponse(err.to_string()))?;
+        let url = self.watch_url.join(path.path()).map_err(|err| MetadataError::InvalidResponse(err.to_string()))?;
+        let response = self.client.get(url)
+            .header(reqwest::header::ACCEPT_LANGUAGE, "en")
+            .send().await?;
+        if !response.status().is_success() {
+            return Err(MetadataError::from_status(response.status()));
+        }
+        Ok(extract_channel_id(&response.text().await?))
+    }
+}
+
+/// Ищет ID канала в HTML его страницы.
+fn extract_channel_id(page: &str) -> Option<String> {
+    let candidates = [
+        // ytInitialData: "externalId":"UC..."
+        page.split_once("\"externalId\":\"").map(|(_, rest)| rest),
+        // <link rel="canonical" href="https://www.youtube.com/channel/UC...">
+        page.split_once("<link rel=\"canonical\" href=\"https://www.youtube.com/channel/").map(|(_, rest)| rest),
+        // <meta itemprop="identifier" content="UC...">
+        page.split_once("itemprop=\"identifier\" content=\"").map(|(_, rest)| rest),
+    ];
+    candidates.into_iter().flatten()
+        .filter_map(|rest| rest.split('"').next())
+        .find(|id| crate::is_channel_id(id))
+        .map(str::to_string)
+}
+
+/// Ищет длительность в HTML страницы просмотра.
+fn extract_page_duration(page: &str) -> Option<u32> {
+    // ytInitialPlayerResponse: "lengthSeconds":"754"
+    if let Some((_, rest)) = page.split_once("\"lengthSeconds\":\"") {
+        if let Some(seconds) = rest.split('"').next().and_then(|s| s.parse().ok()) {
+            return Some(seconds);
+        }
+    }
+    // <meta itemprop="duration" content="PT12M34S">
+    let (_, rest) = page.split_once("itemprop=\"duration\" content=\"")?;
+    parse_iso8601_duration(rest.split('"').next()?)
+}
+
+/// Разбирает ISO 8601 длительность вида `PT1H2M3S`.
+fn parse_iso8601_duration(value: &str) -> Option<u32> {
+    let value = value.strip_prefix("PT")?;
+    let mut total: u32 = 0;
+    let mut number = String::new();
+    for c in value.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let n: u32 = number.parse().ok()?;
+        number.clear();
+        total = total.checked_add(match c {
+            'H' => n.checked_mul(3600)?,
+            'M' => n.checked_mul(60)?,
+            'S' => n,
+            _ => return None,
+        })?;
+    }
+    number.is_empty().then_some(total)
+}
+
+// ------------------------
+// Invidious
+// ------------------------
+
+/// API Invidious (`/api/v1/videos/<id>`).
+pub struct InvidiousProvider {
+    base_url: Url,
+    client: reqwest::Client,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InvidiousVideo {
+    title: String,
+    author: Option<String>,
+    author_url: Option<String>,
+    length_seconds: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct InvidiousError {
+    error: String,
+}
+
+#[derive(Deserialize)]
+struct InvidiousResolvedUrl {
+    ucid: Option<String>,
+}
+
+impl InvidiousProvider {
+    pub fn new(base_url: Url) -> Self {
+        Self { base_url, client: reqwest::Client::new() }
+    }
+
+    fn request_url(&self, vid: &str) -> Result<Url, url::ParseError> {
+        let mut url = self.base_url.join(&format!("api/v1/videos/{vid}"))?;
+        url.query_pairs_mut().append_pair("fields", "title,author,authorUrl,lengthSeconds");
+        Ok(url)
+    }
+}
+
+impl From<(InvidiousVideo, &str)> for VideoMetadata {
+    fn from((video, vid): (InvidiousVideo, &str)) -> Self {
+        VideoMetadata {
+            title: video.title,
+            author_name: video.author,
+            // Invidious отдаёт относительный путь (/channel/UC...)
+            author_url: video.author_url.map(|path| format!("https://www.youtube.com{path}")),
+            // Превью инстанса могут быть относительными, поэтому берём напрямую с YouTube
+            thumbnail_url: Some(format!("https://i.ytimg.com/vi/{vid}/hqdefault.jpg")),
+            duration: video.length_seconds.filter(|&length| length != 0),
+        }
+    }
+}
+
+#[async_trait]
+impl MetadataProvider for InvidiousProvider {
+    async fn metadata(&self, vid: &str) -> Result<VideoMetadata, MetadataError> {
+        let url = self.request_url(vid).map_err(|err| MetadataError::InvalidResponse(err.to_string()))?;
+        let response = self.client.get(url).send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            // Invidious объясняет причину в теле ответа
+            let reason = response.json::<InvidiousError>().await.ok()
+                .and_then(|body| MetadataError::from_message(&body.error));
+            return Err(reason.unwrap_or_else(|| MetadataError::from_status(status)));
+        }
+        let video: InvidiousVideo = response.json().await?;
+        Ok(normalize((video, vid).into()))
+    }
+
+    async fn channel_id(&self, url: &str) -> Result<Option<String>, MetadataError> {
+        let mut request = self.base_url.join("api/v1/resolveurl").map_err(|err| MetadataError::InvalidResponse(err.to_string()))?;
+        request.query_pairs_mut().append_pair("url", url);
+        let response = self.client.get(request).send().await?;
+        if !response.status().is_success() {
+            return Err(MetadataError::from_status(response.status()));
+        }
+        let resolved: InvidiousResolvedUrl = response.json().await?;
+        Ok(resolved.ucid.filter(|id| crate::is_channel_id(id)))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InvidiousSearchItem {
+    video_id: String,
+    title: String,
+    author: Option<String>,
+    length_seconds: Option<u32>,
+}
+
+impl From<InvidiousSearchItem> for SearchResult {
+    fn from(item: InvidiousSearchItem) -> Self {
+        SearchResult {
+            id: item.video_id,
+            title: item.title,
+            author_name: item.author,
+            duration: item.length_seconds.filter(|&length| length != 0),
+        }
+    }
+}
+
+#[async_trait]
+impl SearchProvider for InvidiousProvider {
+    async fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchResult>, MetadataError> {
+        let mut url = self.base_url.join("api/v1/search").map_err(|err| MetadataError::InvalidResponse(err.to_string()))?;
+        url.query_pairs_mut()
+            .append_pair("q", query)
+            .append_pair("type", "video")
+            .append_pair("fields", "videoId,title,author,lengthSeconds");
+        let response = self.client.get(url).send().await?;
+        if !response.status().is_success() {
+            return Err(MetadataError::from_status(response.status()));
+        }
+        let items: Vec<InvidiousSearchItem> = response.json().await?;
+        Ok(items.into_iter().take(limit).map(SearchResult::from).collect())
+    }
+}
+
+// ------------------------
+// OpenGraph
+// ------------------------
+
+/// Метаданные из OpenGraph разметки страницы видео (Twitch, VK Видео).
+pub struct OpenGraphProvider {
+    platform: Platform,
+    client: reqwest::Client,
+}
+
+impl OpenGraphProvider {
+    pub fn new(platform: Platform) -> Self {
+        Self { platform, client: reqwest::Client::new() }
+    }
+}
+
+#[async_trait]
+impl MetadataProvider for OpenGraphProvider {
+    async fn metadata(&self, vid: &str) -> Result<VideoMetadata, MetadataError> {
+        let response = self.client.get(self.platform.video_url(vid, None))
+            // Без браузерного User-Agent некоторые платформы отдают пустую заглушку
+            .header(reqwest::header::USER_AGENT, "Mozilla/5.0 (compatible; doggy-watch)")
+            .header(reqwest::header::ACCEPT_LANGUAGE, "ru")
+            .send().await?;
+        if !response.status().is_success() {
+            return Err(MetadataError::from_status(response.status()));
+        }
+        let page = response.text().await?;
+        // Удалённые видео обычно отдают страницу-заглушку без og:title
+        let metadata = extract_open_graph(&page).ok_or(MetadataError::NotFound)?;
+        Ok(normalize(metadata))
+    }
+}
+
+/// Собирает метаданные из `<meta property="og:..." content="...">`.
+fn extract_open_graph(page: &str) -> Option<VideoMetadata> {
+    let mut tags: HashMap<String, String> = HashMap::new();
+    for tag in page.split("<meta").skip(1) {
+        let tag = tag.split('>').next().unwrap_or_default();
+        let key = html_attribute(tag, "property").or_else(|| html_attribute(tag, "name"));
+        if let (Some(key), Some(content)) = (key, html_attribute(tag, "content")) {
+            tags.entry(key).or_insert(content);
+        }
+    }
+    let title = tags.remove("og:title").filter(|title| !title.is_empty())?;
+    let duration = ["og:video:duration", "video:duration"].iter()
+        .find_map(|key| tags.get(*key)?.parse().ok())
+        .or_else(|| parse_iso8601_duration(tags.get("duration")?));
+    Some(VideoMetadata {
+        title,
+        author_name: None,
+        author_url: None,
+        thumbnail_url: tags.remove("og:image"),
+        duration: duration.filter(|&duration| duration != 0),
+    })
+}
+
+/// Значение атрибута HTML тега с расшифровкой основных сущностей.
+fn html_attribute(tag: &str, name: &str) -> Option<String> {
+    let (_, rest) = tag.split_once(&format!("{name}=\""))?;
+    let value = rest.split('"').next()?;
+    Some(value
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#039;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&"))
+}
+
+// ------------------------
+// Rutube
+// ------------------------
+
+/// API Rutube (`/api/video/<id>/`).
+pub struct RutubeProvider {
+    base_url: Url,
+    client: reqwest::Client,
+}
+
+#[derive(Deserialize)]
+struct RutubeVideo {
+    title: String,
+    duration: Option<u32>,
+    thumbnail_url: Option<String>,
+    author: Option<RutubeAuthor>,
+}
+
+#[derive(Deserialize)]
+struct RutubeAuthor {
+    name: Option<String>,
+    site_url: Option<String>,
+}
+
+impl RutubeProvider {
+    pub fn new(base_url: Url) -> Self {
+        Self { base_url, client: reqwest::Client::new() }
+    }
+
+    fn request_url(&self, vid: &str) -> Result<Url, url::ParseError> {
+        self.base_url.join(&format!("video/{vid}/"))
+    }
+}
+
+impl Default for RutubeProvider {
+    fn default() -> Self {
+        Self::new(DEFAULT_RUTUBE_API_URL.parse().expect("Failed to parse default Rutube url"))
+    }
+}
+
+impl From<RutubeVideo> for VideoMetadata {
+    fn from(video: RutubeVideo) -> Self {
+        let (author_name, author_url) = video.author
+            .map(|author| (author.name, author.site_url))
+            .unwrap_or_default();
+        VideoMetadata {
+            title: video.title,
+            author_name,
+            author_url,
+            thumbnail_url: video.thumbnail_url,
+            duration: video.duration.filter(|&duration| duration != 0),
+        }
+    }
+}
+
+#[async_trait]
+impl MetadataProvider for RutubeProvider {
+    async fn metadata(&self, vid: &str) -> Result<VideoMetadata, MetadataError> {
+        let url = self.request_url(vid).map_err(|err| MetadataError::InvalidResponse(err.to_string()))?;
+        let response = self.client.get(url).send().await?;
+        if !response.status().is_success() {
+            return Err(MetadataError::from_status(response.status()));
+        }
+        let video: RutubeVideo = response.json().await?;
+        Ok(normalize(video.into()))
+    }
+}
+
+// ------------------------
+// Providers
+// ------------------------
+
+/// Источники метаданных по платформам.
+#[derive(Default)]
+pub struct Providers {
+    providers: HashMap<Platform, Box<dyn MetadataProvider>>,
+}
+
+impl Providers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with<P: MetadataProvider + 'static>(mut self, platform: Platform, provider: P) -> Self {
+        self.providers.insert(platform, Box::new(provider));
+        self
+    }
+
+    pub async fn metadata(&self, platform: Platform, vid: &str) -> Result<VideoMetadata, MetadataError> {
+        let provider = self.providers.get(&platform).ok_or(MetadataError::Unsupported)?;
+        provider.metadata(vid).await
+    }
+
+    pub async fn channel_id(&self, platform: Platform, url: &str) -> Result<Option<String>, MetadataError> {
+        match self.providers.get(&platform) {
+            Some(provider) => provider.channel_id(url).await,
+            None => Ok(None),
+        }
+    }
+}
+
+// ------------------------
+// Retry
+// ------------------------
+
+/// Повторяет запрос при временных ошибках с экспоненциальной задержкой.
+pub struct Retry<P> {
+    inner: P,
+    attempts: u32,
+    base_delay: Duration,
+}
+
+impl<P> Retry<P> {
+    pub fn new(inner: P, attempts: u32, base_delay: Duration) -> Self {
+        Self { inner, attempts: attempts.max(1), base_delay }
+    }
+
+    async fn run<T, F, Fut>(&self, what: &str, request: F) -> Result<T, MetadataError>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T, MetadataError>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match request().await {
+                Err(err) if err.is_transient() && attempt < self.attempts => {
+                    let delay = self.base_delay * 2u32.pow(attempt - 1);
+                    tracing::warn!("{what} failed ({err}), retrying in {delay:?}");
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                },
+                result => return result,
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<P: MetadataProvider> MetadataProvider for Retry<P> {
+    async fn metadata(&self, vid: &str) -> Result<VideoMetadata, MetadataError> {
+        self.run(&format!("Metadata request for {vid}"), || self.inner.metadata(vid)).await
+    }
+
+    async fn channel_id(&self, url: &str) -> Result<Option<String>, MetadataError> {
+        self.run(&format!("Channel ID request for {url}"), || self.inner.channel_id(url)).await
+    }
+}
+
+#[async_trait]
+impl<P: SearchProvider> SearchProvider for Retry<P> {
+    async fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchResult>, MetadataError> {
+        self.run(&format!("Search for {query:?}"), || self.inner.search(query, limit)).await
+    }
+}
+
+// ------------------------
+// WithDuration
+// ------------------------
+
+/// Дополняет метаданные длительностью из другого источника, если основной её не знает.
+/// Ошибки дополнительного источника не критичны: видео просто останется без длительности.
+pub struct WithDuration<P, D> {
+    inner: P,
+    durations: D,
+}
+
+impl<P, D> WithDuration<P, D> {
+    pub fn new(inner: P, durations: D) -> Self {
+        Self { inner, durations }
+    }
+}
+
+#[async_trait]
+impl<P: MetadataProvider, D: MetadataProvider> MetadataProvider for WithDuration<P, D> {
+    async fn metadata(&self, vid: &str) -> Result<VideoMetadata, MetadataError> {
+        let mut metadata = self.inner.metadata(vid).await?;
+        if metadata.duration.is_none() {
+            match self.durations.metadata(vid).await {
+                Ok(extra) => metadata.duration = extra.duration,
+                Err(err) => tracing::debug!("Can't find duration of {vid}: {err}"),
+            }
+        }
+        Ok(metadata)
+    }
+
+    async fn channel_id(&self, url: &str) -> Result<Option<String>, MetadataError> {
+        match self.inner.channel_id(url).await? {
+            Some(id) => Ok(Some(id)),
+            None => self.durations.channel_id(url).await,
+        }
+    }
+}
+
+// ------------------------
+// Fake
+// ------------------------
+
+/// Источник без сети: для тестов и локального запуска.
+#[derive(Default)]
+pub struct FakeProvider {
+    videos: HashMap<String, VideoMetadata>,
+    /// Нормализованная ссылка на канал -> ID
+    channels: HashMap<String, String>,
+    /// Генерировать метаданные для неизвестных видео вместо ошибки
+    generate_missing: bool,
+}
+
+impl FakeProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Отвечает на любой ID сгенерированными метаданными.
+    pub fn permissive() -> Self {
+        Self { generate_missing: true, ..Default::default() }
+    }
+
+    pub fn with(mut self, vid: &str, metadata: VideoMetadata) -> Self {
+        self.videos.insert(vid.to_string(), metadata);
+        self
+    }
+
+    pub fn with_channel(mut self, url: &str, id: &str) -> Self {
+        self.channels.insert(url.to_string(), id.to_string());
+        self
+    }
+}
+
+#[async_trait]
+impl MetadataProvider for FakeProvider {
+    async fn metadata(&self, vid: &str) -> Result<VideoMetadata, MetadataError> {
+        if let Some(metadata) = self.videos.get(vid) {
+            Ok(normalize(metadata.clone()))
+        } else if self.generate_missing {
+            Ok(VideoMetadata {
+                title: format!("Video {vid}"),
+                author_name: Some("Fake channel".to_string()),
+                ..Default::default()
+            })
+        } else {
+            Err(MetadataError::NotFound)
+        }
+    }
+
+    async fn channel_id(&self, url: &str) -> Result<Option<String>, MetadataError> {
+        Ok(self.channels.get(url).cloned())
+    }
+}
+
+#[async_trait]
+impl SearchProvider for FakeProvider {
+    /// Ищет по названиям известных видео, в разрешающем режиме генерирует результаты.
+    async fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchResult>, MetadataError> {
+        let query = query.to_lowercase();
+        let mut results: Vec<SearchResult> = self.videos.iter()
+            .filter(|(_, metadata)| metadata.title.to_lowercase().contains(&query))
+            .map(|(vid, metadata)| SearchResult {
+                id: vid.clone(),
+                title: metadata.title.clone(),
+                author_name: metadata.author_name.clone(),
+                duration: metadata.duration,
+            })
+            .collect();
+        results.sort_unstable_by(|a, b| a.id.cmp(&b.id));
+        if results.is_empty() && self.generate_missing {
+            results = (1..=limit).map(|n| SearchResult {
+                id: format!("fakevideo{n:02}"),
+                title: format!("{query} #{n}"),
+                author_name: Some("Fake channel".to_string()),
+                duration: None,
+            }).collect();
+        }
+        results.truncate(limit);
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_oembed_request_url() {
+        let provider = OEmbedProvider::default();
+        assert_eq!(
+            provider.request_url("VJFNcHgQ4HM").as_str(),
+            "https://www.youtube.com/oembed?url=https%3A%2F%2Fyoutu.be%2FVJFNcHgQ4HM&format=json"
+        );
+    }
+
+    #[test]
+    fn test_watch_page_request_url() {
+        let provider = WatchPageProvider::new("http://localhost:8080/watch".parse().unwrap());
+        assert_eq!(provider.request_url("VJFNcHgQ4HM").as_str(), "http://localhost:8080/watch?v=VJFNcHgQ4HM");
+    }
+
+    #[tokio::test]
+    async fn test_with_duration() {
+        let meta = |duration| VideoMetadata { title: "Video".to_string(), duration, ..Default::default() };
+        let provider = WithDuration::new(
+            FakeProvider::new().with("VJFNcHgQ4HM", meta(None)).with("rfDBTQNdj-M", meta(Some(10))),
+            FakeProvider::new().with("VJFNcHgQ4HM", meta(Some(754))).with("rfDBTQNdj-M", meta(Some(754))),
+        );
+        assert_eq!(provider.metadata("VJFNcHgQ4HM").await.unwrap().duration, Some(754));
+        assert_eq!(provider.metadata("rfDBTQNdj-M").await.unwrap().duration, Some(10));
+
+        // Длительность не нашлась, но метаданные всё равно есть
+        let provider = WithDuration::new(FakeProvider::new().with("VJFNcHgQ4HM", meta(None)), flaky(1, || MetadataError::RateLimited));
+        assert_eq!(provider.metadata("VJFNcHgQ4HM").await.unwrap(), meta(None));
+    }
+
+    #[test]
+    fn test_extract_channel_id() {
+        let page = r#"<script>var ytInitialData = {"metadata":{"channelMetadataRenderer":{"externalId":"UCX6OQ3DkcsbYNE6H8uQQuVA"}}};</script>"#;
+        assert_eq!(extract_channel_id(page).as_deref(), Some("UCX6OQ3DkcsbYNE6H8uQQuVA"));
+        let page = r#"<link rel="canonical" href="https://www.youtube.com/channel/UCX6OQ3DkcsbYNE6H8uQQuVA">"#;
+        assert_eq!(extract_channel_id(page).as_deref(), Some("UCX6OQ3DkcsbYNE6H8uQQuVA"));
+        assert_eq!(extract_channel_id(r#""externalId":"nope""#), None);
+        assert_eq!(extract_channel_id("<html></html>"), None);
+    }
+
+    #[test]
+    fn test_parse_iso8601_duration() {
+        assert_eq!(parse_iso8601_duration("PT12M34S"), Some(754));
+        assert_eq!(parse_iso8601_duration("PT1H2M3S"), Some(3723));
+        assert_eq!(parse_iso8601_duration("PT45S"), Some(45));
+        assert_eq!(parse_iso8601_duration("PT2H"), Some(7200));
+        assert_eq!(parse_iso8601_duration("PT0M0S"), Some(0));
+        assert_eq!(parse_iso8601_duration("P1D"), None);
+        assert_eq!(parse_iso8601_duration("PT12"), None);
+        assert_eq!(parse_iso8601_duration(""), None);
+        assert_eq!(parse_iso8601_duration("PT9999999H"), None);
+        assert_eq!(parse_iso8601_duration("PT1000000H1000000H"), None);
+    }
+
+    #[test]
+    fn test_extract_page_duration() {
+        let page = r#"<script>var ytInitialPlayerResponse = {"videoDetails":{"videoId":"VJFNcHgQ4HM","lengthSeconds":"754","keywords":[]}};</script>"#;
+        assert_eq!(extract_page_duration(page), Some(754));
+        let page = r#"<meta itemprop="name" content="Video"><meta itemprop="duration" content="PT1H2M3S">"#;
+        assert_eq!(extract_page_duration(page), Some(3723));
+        assert_eq!(extract_page_duration("<html></html>"), None);
+    }
+
+    #[test]
+    fn test_invidious_request_url() {
+        let provider = InvidiousProvider::new("https://yewtu.be/".parse().unwrap());
+        assert_eq!(
+            provider.request_url("VJFNcHgQ4HM").unwrap().as_str(),
+            "https://yewtu.be/api/v1/videos/VJFNcHgQ4HM?fields=title%2Cauthor%2CauthorUrl%2ClengthSeconds"
+        );
+    }
+
+    #[test]
+    fn test_invidious_video_to_metadata() {
+        let json = r#"{"title": "a/b", "author": "Channel", "authorUrl": "/channel/UC123", "lengthSeconds": 754}"#;
+        let video: InvidiousVideo = serde_json::from_str(json).unwrap();
+        let meta = normalize((video, "VJFNcHgQ4HM").into());
+        assert_eq!(meta, VideoMetadata {
+            title: "a/ b".to_string(),
+            author_name: Some("Channel".to_string()),
+            author_url: Some("https://www.youtube.com/channel/UC123".to_string()),
+            thumbnail_url: Some("https://i.ytimg.com/vi/VJFNcHgQ4HM/hqdefault.jpg".to_string()),
+            duration: Some(754),
+        });
+    }
+
+    #[test]
+    fn test_extract_open_graph() {
+        let page = r#"<head><meta property="og:title" content="Clip &quot;title&quot;">
+            <meta content="https://example.com/preview.jpg" property="og:image" />
+            <meta property="og:video:duration" content="42"></head>"#;
+        assert_eq!(extract_open_graph(page), Some(VideoMetadata {
+            title: "Clip \"title\"".to_string(),
+            thumbnail_url: Some("https://example.com/preview.jpg".to_string()),
+            duration: Some(42),
+            ..Default::default()
+        }));
+        let page = r#"<meta name="og:title" content="VK"><meta itemprop="duration" name="duration" content="PT1M30S">"#;
+        assert_eq!(extract_open_graph(page).unwrap().duration, Some(90));
+        assert_eq!(extract_open_graph(r#"<meta property="og:title" content="">"#), None);
+        assert_eq!(extract_open_graph("<html></html>"), None);
+    }
+
+    #[test]
+    fn test_rutube_video_to_metadata() {
+        let provider = RutubeProvider::default();
+        assert_eq!(
+            provider.request_url("c6cc4d620b1d4338901770a44b3e82f4").unwrap().as_str(),
+            "https://rutube.ru/api/video/c6cc4d620b1d4338901770a44b3e82f4/"
+        );
+        let json = r#"{"title": "Video", "duration": 754, "thumbnail_url": "https://pic.rutube.ru/1.jpg", "author": {"name": "Channel", "site_url": "https://rutube.ru/channel/1/"}}"#;
+        let video: RutubeVideo = serde_json::from_str(json).unwrap();
+        assert_eq!(VideoMetadata::from(video), VideoMetadata {
+            title: "Video".to_string(),
+            author_name: Some("Channel".to_string()),
+            author_url: Some("https://rutube.ru/channel/1/".to_string()),
+            thumbnail_url: Some("https://pic.rutube.ru/1.jpg".to_string()),
+            duration: Some(754),
+        });
+    }
+
+    #[tokio::test]
+    async fn test_providers_routing() {
+        let meta = VideoMetadata { title: "Known".to_string(), ..Default::default() };
+        let providers = Providers::new().with(Platform::YouTube, FakeProvider::new().with("VJFNcHgQ4HM", meta.clone()));
+        assert_eq!(providers.metadata(Platform::YouTube, "VJFNcHgQ4HM").await.unwrap(), meta);
+        assert!(matches!(providers.metadata(Platform::Rutube, "VJFNcHgQ4HM").await, Err(MetadataError::Unsupported)));
+    }
+
+    #[tokio::test]
+    async fn test_fake_provider() {
+        let meta = VideoMetadata { title: "Known".to_string(), ..Default::default() };
+        let provider = FakeProvider::new().with("VJFNcHgQ4HM", meta.clone());
+        assert_eq!(provider.metadata("VJFNcHgQ4HM").await.unwrap(), meta);
+        assert!(provider.metadata("rfDBTQNdj-M").await.is_err());
+
+        let provider = FakeProvider::permissive();
+        assert_eq!(provider.metadata("rfDBTQNdj-M").await.unwrap().title, "Video rfDBTQNdj-M");
+    }
+
+    #[tokio::test]
+    async fn test_fake_search() {
+        let meta = |title: &str| VideoMetadata { title: title.to_string(), ..Default::default() };
+        let provider = FakeProvider::new()
+            .with("VJFNcHgQ4HM", meta("Doggy Dox best moments"))
+            .with("rfDBTQNdj-M", meta("Cooking stream"));
+        let results = provider.search("doggy", 5).await.unwrap();
+        assert_eq!(results.iter().map(|r| r.id.as_str()).collect::<Vec<_>>(), ["VJFNcHgQ4HM"]);
+        assert!(provider.search("nothing", 5).await.unwrap().is_empty());
+
+        let results = FakeProvider::permissive().search("anything", 3).await.unwrap();
+        assert_eq!(results.len(), 3);
+        assert!(results.iter().all(|result| crate::is_valid_video_id(&result.id)));
+    }
+
+    #[test]
+    fn test_invidious_search_item() {
+        let json = r#"[{"videoId": "VJFNcHgQ4HM", "title": "Video", "author": "Channel", "lengthSeconds": 0}]"#;
+        let items: Vec<InvidiousSearchItem> = serde_json::from_str(json).unwrap();
+        let results: Vec<SearchResult> = items.into_iter().map(SearchResult::from).collect();
+        assert_eq!(results, [SearchResult {
+            id: "VJFNcHgQ4HM".to_string(),
+            title: "Video".to_string(),
+            author_name: Some("Channel".to_string()),
+            duration: None,
+        }]);
+    }
+
+    /// Отвечает ошибкой `failures` раз, затем отдаёт метаданные.
+    struct FlakyProvider {
+        failures: std::sync::atomic::AtomicU32,
+        error: fn() -> MetadataError,
+    }
+
+    #[async_trait]
+    impl MetadataProvider for FlakyProvider {
+        async fn metadata(&self, vid: &str) -> Result<VideoMetadata, MetadataError> {
+            use std::sync::atomic::Ordering;
+            if self.failures.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1)).is_ok() {
+                Err((self.error)())
+            } else {
+                Ok(VideoMetadata { title: vid.to_string(), ..Default::default() })
+            }
+        }
+    }
+
+    fn flaky(failures: u32, error: fn() -> MetadataError) -> FlakyProvider {
+        FlakyProvider { failures: failures.into(), error }
+    }
+
+    #[tokio::test]
+    async fn test_retry_transient() {
+        let provider = Retry::new(flaky(2, || MetadataError::RateLimited), 3, Duration::from_millis(1));
+        assert_eq!(provider.metadata("VJFNcHgQ4HM").await.unwrap().title, "VJFNcHgQ4HM");
+
+        let provider = Retry::new(flaky(3, || MetadataError::RateLimited), 3, Duration::from_millis(1));
+        assert!(matches!(provider.metadata("VJFNcHgQ4HM").await, Err(MetadataError::RateLimited)));
+    }
+
+    #[tokio::test]
+    async fn test_retry_permanent() {
+        let provider = Retry::new(flaky(1, || MetadataError::Private), 3, Duration::from_millis(1));
+        assert!(matches!(provider.metadata("VJFNcHgQ4HM").await, Err(MetadataError::Private)));
+    }
+}
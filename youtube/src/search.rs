@@ -0,0 +1,160 @@
+use async_trait::async_trait;
+use serde_json::Value;
+use url::Url;
+
+use crate::MetadataError;
+
+pub const DEFAULT_SEARCH_URL: &str = "https://www.youtube.com/results";
+
+/// Найденное по названию видео.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResult {
+    pub id: String,
+    pub title: String,
+    pub author_name: Option<String>,
+    /// Длительность в секундах (у трансляций её нет)
+    pub duration: Option<u32>,
+}
+
+/// Поиск YouTube видео по тексту.
+#[async_trait]
+pub trait SearchProvider: Send + Sync {
+    async fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchResult>, MetadataError>;
+}
+
+/// Поиск через страницу результатов YouTube (данные из `ytInitialData`).
+pub struct YouTubeSearchProvider {
+    base_url: Url,
+    client: reqwest::Client,
+}
+
+impl YouTubeSearchProvider {
+    pub fn new(base_url: Url) -> Self {
+        Self { base_url, client: reqwest::Client::new() }
+    }
+
+    fn request_url(&self, query: &str) -> Url {
+        let mut url = self.base_url.clone();
+        url.query_pairs_mut()
+            .append_pair("search_query", query)
+            // Фильтр "Тип: видео"
+            .append_pair("sp", "EgIQAQ==");
+        url
+    }
+}
+
+impl Default for YouTubeSearchProvider {
+    fn default() -> Self {
+        Self::new(DEFAULT_SEARCH_URL.parse().expect("Failed to parse default search url"))
+    }
+}
+
+#[async_trait]
+impl SearchProvider for YouTubeSearchProvider {
+    async fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchResult>, MetadataError> {
+        let response = self.client.get(self.request_url(query))
+            .header(reqwest::header::ACCEPT_LANGUAGE, "ru")
+            .send().await?;
+        if !response.status().is_success() {
+            return Err(MetadataError::from_status(response.status()));
+        }
+        let page = response.text().await?;
+        let mut results = extract_search_results(&page)
+            .ok_or(MetadataError::InvalidResponse("ytInitialData not found".to_string()))?;
+        results.truncate(limit);
+        Ok(results)
+    }
+}
+
+/// Достаёт результаты из `var ytInitialData = {...};` страницы поиска.
+fn extract_search_results(page: &str) -> Option<Vec<SearchResult>> {
+    let (_, rest) = page.split_once("ytInitialData = ")?;
+    let (json, _) = rest.split_once(";</script>")?;
+    let data: Value = serde_json::from_str(json).ok()?;
+    let mut results = Vec::new();
+    collect_video_renderers(&data, &mut results);
+    Some(results)
+}
+
+fn collect_video_renderers(value: &Value, results: &mut Vec<SearchResult>) {
+    match value {
+        Value::Object(map) => {
+            if let Some(result) = map.get("videoRenderer").and_then(parse_video_renderer) {
+                results.push(result);
+                return;
+            }
+            map.values().for_each(|value| collect_video_renderers(value, results));
+        },
+        Value::Array(values) => values.iter().for_each(|value| collect_video_renderers(value, results)),
+        _ => (),
+    }
+}
+
+fn parse_video_renderer(renderer: &Value) -> Option<SearchResult> {
+    let text = |key: &str| -> Option<String> {
+        let field = renderer.get(key)?;
+        field.get("simpleText")
+            .or_else(|| field.get("runs")?.get(0)?.get("text"))?
+            .as_str().map(str::to_string)
+    };
+    Some(SearchResult {
+        id: renderer.get("videoId")?.as_str()?.to_string(),
+        title: text("title")?,
+        author_name: text("ownerText"),
+        duration: text("lengthText").as_deref().and_then(parse_clock),
+    })
+}
+
+/// Разбирает длительность вида `12:34` или `1:02:03`.
+fn parse_clock(value: &str) -> Option<u32> {
+    value.split(':').try_fold(0u32, |total, part| {
+        total.checked_mul(60)?.checked_add(part.parse().ok()?)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_request_url() {
+        let provider = YouTubeSearchProvider::default();
+        assert_eq!(
+            provider.request_url("doggy dox").as_str(),
+            "https://www.youtube.com/results?search_query=doggy+dox&sp=EgIQAQ%3D%3D"
+        );
+    }
+
+    #[test]
+    fn test_parse_clock() {
+        assert_eq!(parse_clock("12:34"), Some(754));
+        assert_eq!(parse_clock("1:02:03"), Some(3723));
+        assert_eq!(parse_clock("45"), Some(45));
+        assert_eq!(parse_clock("LIVE"), None);
+        assert_eq!(parse_clock(""), None);
+    }
+
+    #[test]
+    fn test_extract_search_results() {
+        let page = r#"<script>var ytInitialData = {"contents":{"sectionListRenderer":{"contents":[{"itemSectionRenderer":{"contents":[
+            {"videoRenderer":{"videoId":"VJFNcHgQ4HM","title":{"runs":[{"text":"First"}]},"ownerText":{"runs":[{"text":"Channel"}]},"lengthText":{"simpleText":"12:34"}}},
+            {"shelfRenderer":{"title":{"simpleText":"Shelf"}}},
+            {"videoRenderer":{"videoId":"rfDBTQNdj-M","title":{"runs":[{"text":"Live"}]}}}
+        ]}}]}}};</script>"#;
+        assert_eq!(extract_search_results(page), Some(vec![
+            SearchResult {
+                id: "VJFNcHgQ4HM".to_string(),
+                title: "First".to_string(),
+                author_name: Some("Channel".to_string()),
+                duration: Some(754),
+            },
+            SearchResult {
+                id: "rfDBTQNdj-M".to_string(),
+                title: "Live".to_string(),
+                author_name: None,
+                duration: None,
+            },
+        ]));
+        assert_eq!(extract_search_results("<html></html>"), None);
+    }
+}